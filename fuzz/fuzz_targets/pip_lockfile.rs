@@ -0,0 +1,9 @@
+#![no_main]
+
+use deterministic_engine::verifier::lockfile::{LockfileParser, PipLockfileParser};
+use libfuzzer_sys::fuzz_target;
+
+// Arbitrary bytes must produce `Ok` or `Err`, never a panic
+fuzz_target!(|data: &[u8]| {
+    let _ = PipLockfileParser.parse_reader(data);
+});
@@ -0,0 +1,151 @@
+//! Comparison of audit reports produced on different machines
+//!
+//! A reproducibility gate runs the same workload twice and requires the seeds
+//! and computation counts to match exactly. Timings are compared with a
+//! relative tolerance, since they legitimately vary between hosts.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+/// Default relative tolerance for per-operation timing differences
+pub const DEFAULT_TIMING_TOLERANCE: f64 = 0.5;
+
+/// A per-operation mean timing that differs beyond the tolerance
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingMismatch {
+    pub operation: String,
+    pub left_ns: Option<u64>,
+    pub right_ns: Option<u64>,
+}
+
+/// Differences found between two audit reports
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AuditDiff {
+    pub seed: Option<(u64, u64)>,
+    pub computation_count: Option<(u64, u64)>,
+    pub timings: Vec<TimingMismatch>,
+}
+
+impl AuditDiff {
+    /// True when the two reports agree on every compared field
+    pub fn is_match(&self) -> bool {
+        self.seed.is_none() && self.computation_count.is_none() && self.timings.is_empty()
+    }
+}
+
+/// Compare two audit reports using the default timing tolerance
+pub fn diff_audit_reports(a: &str, b: &str) -> Result<AuditDiff, Box<dyn std::error::Error>> {
+    diff_audit_reports_with_tolerance(a, b, DEFAULT_TIMING_TOLERANCE)
+}
+
+/// Compare two audit reports, allowing timings to differ by `timing_tolerance` (relative)
+pub fn diff_audit_reports_with_tolerance(
+    a: &str,
+    b: &str,
+    timing_tolerance: f64,
+) -> Result<AuditDiff, Box<dyn std::error::Error>> {
+    let left = engine_section(a)?;
+    let right = engine_section(b)?;
+    let mut diff = AuditDiff::default();
+
+    let (left_seed, right_seed) = (required_u64(&left, "seed")?, required_u64(&right, "seed")?);
+    if left_seed != right_seed {
+        diff.seed = Some((left_seed, right_seed));
+    }
+
+    let (left_count, right_count) = (
+        required_u64(&left, "total_computations")?,
+        required_u64(&right, "total_computations")?,
+    );
+    if left_count != right_count {
+        diff.computation_count = Some((left_count, right_count));
+    }
+
+    let left_timings = operation_timings(&left);
+    let right_timings = operation_timings(&right);
+    let mut operations: Vec<&String> = left_timings.keys().chain(right_timings.keys()).collect();
+    operations.sort();
+    operations.dedup();
+
+    for operation in operations {
+        let left_ns = left_timings.get(operation).copied();
+        let right_ns = right_timings.get(operation).copied();
+        let within_tolerance = match (left_ns, right_ns) {
+            (Some(l), Some(r)) => {
+                let baseline = l.max(r).max(1) as f64;
+                (l.abs_diff(r) as f64) / baseline <= timing_tolerance
+            }
+            _ => false,
+        };
+        if !within_tolerance {
+            diff.timings.push(TimingMismatch {
+                operation: operation.clone(),
+                left_ns,
+                right_ns,
+            });
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Extracts the `deterministic_engine` object from a report
+fn engine_section(report: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut parsed: Value = serde_json::from_str(report)?;
+    parsed
+        .get_mut("deterministic_engine")
+        .map(Value::take)
+        .ok_or_else(|| "Audit report has no deterministic_engine section".into())
+}
+
+/// Reads a mandatory unsigned integer field
+fn required_u64(section: &Value, field: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    section
+        .get(field)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| format!("Audit report is missing '{}'", field).into())
+}
+
+/// Reads the optional per-operation mean timings
+fn operation_timings(section: &Value) -> BTreeMap<String, u64> {
+    section
+        .get("operation_timings_ns")
+        .and_then(Value::as_object)
+        .map(|timings| {
+            timings
+                .iter()
+                .filter_map(|(op, ns)| ns.as_u64().map(|ns| (op.clone(), ns)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TEST_SIGNING_KEY;
+    use crate::DeterministicEngine;
+
+    #[test]
+    fn test_diff_flags_seed_mismatch() {
+        let mut first = DeterministicEngine::new(Some(1), &TEST_SIGNING_KEY);
+        let mut second = DeterministicEngine::new(Some(2), &TEST_SIGNING_KEY);
+        first.compute("fibonacci", &[20]).unwrap();
+        second.compute("fibonacci", &[20]).unwrap();
+
+        // Timings are noisy in tests, so only seed and count are asserted
+        let diff = diff_audit_reports_with_tolerance(
+            &first.export_audit_report(),
+            &second.export_audit_report(),
+            f64::INFINITY,
+        )
+        .unwrap();
+        assert_eq!(diff.seed, Some((1, 2)));
+        assert_eq!(diff.computation_count, None);
+        assert!(!diff.is_match());
+
+        let same = first.export_audit_report();
+        assert!(diff_audit_reports(&same, &same).unwrap().is_match());
+    }
+}
@@ -0,0 +1,94 @@
+//! Bounds-checked big-endian field access for operation inputs
+//!
+//! Operation inputs arrive from untrusted callers, so every fixed-width read
+//! goes through these helpers and fails with an error instead of panicking
+//! when a header points past the end of the buffer.
+
+use crate::error::ComputeError;
+
+/// Copies `N` bytes starting at `offset`, or reports how far short the buffer is
+fn read_array<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N], ComputeError> {
+    offset
+        .checked_add(N)
+        .and_then(|end| bytes.get(offset..end))
+        .map(|slice| {
+            let mut array = [0u8; N];
+            array.copy_from_slice(slice);
+            array
+        })
+        .ok_or(ComputeError::OutOfBounds {
+            offset,
+            width: N,
+            len: bytes.len(),
+        })
+}
+
+/// Reads a big-endian `u32` at `offset`
+pub fn read_u32_be(bytes: &[u8], offset: usize) -> Result<u32, ComputeError> {
+    read_array(bytes, offset).map(u32::from_be_bytes)
+}
+
+/// Reads a big-endian `u64` at `offset`
+pub fn read_u64_be(bytes: &[u8], offset: usize) -> Result<u64, ComputeError> {
+    read_array(bytes, offset).map(u64::from_be_bytes)
+}
+
+/// Reads a big-endian `f64` at `offset`
+pub fn read_f64_be(bytes: &[u8], offset: usize) -> Result<f64, ComputeError> {
+    read_array(bytes, offset).map(f64::from_be_bytes)
+}
+
+/// Writes `value` as big-endian bytes at `offset`
+pub fn write_u64_be(bytes: &mut [u8], offset: usize, value: u64) -> Result<(), ComputeError> {
+    let len = bytes.len();
+    let target = offset
+        .checked_add(8)
+        .and_then(|end| bytes.get_mut(offset..end))
+        .ok_or(ComputeError::OutOfBounds {
+            offset,
+            width: 8,
+            len,
+        })?;
+    target.copy_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_match_reference_vectors() {
+        let bytes = [0x00, 0x00, 0x01, 0x02, 0x3F, 0xF0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(read_u32_be(&bytes, 0), Ok(0x0102));
+        assert_eq!(read_u64_be(&bytes, 4), Ok(0x3FF0_0000_0000_0000));
+        assert_eq!(read_f64_be(&bytes, 4), Ok(1.0));
+
+        let mut buffer = [0u8; 10];
+        write_u64_be(&mut buffer, 2, 0x0102_0304_0506_0708).unwrap();
+        assert_eq!(buffer, [0, 0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_out_of_bounds_offsets_return_errors() {
+        let bytes = [0u8; 8];
+        assert_eq!(
+            read_u64_be(&bytes, 1),
+            Err(ComputeError::OutOfBounds {
+                offset: 1,
+                width: 8,
+                len: 8
+            })
+        );
+        assert!(read_u32_be(&bytes, usize::MAX).is_err());
+        assert_eq!(
+            write_u64_be(&mut [0u8; 8], 4, 1),
+            Err(ComputeError::OutOfBounds {
+                offset: 4,
+                width: 8,
+                len: 8
+            })
+        );
+        assert!(write_u64_be(&mut [0u8; 8], usize::MAX, 1).is_err());
+    }
+}
@@ -0,0 +1,309 @@
+//! Chunked hashing with a commitment after every chunk
+//!
+//! Each chunk is digested with the engine's hasher and becomes one leaf of a
+//! left-balanced Merkle tree: a tree over `n` leaves splits at the largest
+//! power of two below `n`, as in RFC 6962. That shape lets the running root
+//! be kept as a frontier of complete subtrees, so every chunk costs
+//! O(log n) hashes. The commitment returned after chunk `k` equals the final
+//! root of a hasher fed only the first `k` chunks, so partial progress can be
+//! checked later.
+//!
+//! Interior nodes are combined with `proof::hash_combine_with`, so chunk
+//! proofs are ordinary `ProofStep` lists. A verifier also checks that each
+//! step's side matches the chunk's position, which pins a proof to one index.
+//! The engine signs a final commitment as a `"chunked_hash"` result whose
+//! value is `ChunkCommitment::to_bytes`.
+
+use deterministic_engine::hasher::DeterministicHasher;
+use deterministic_engine::verifier::proof::{
+    hash_combine_with, verify_proof_with, ProofStep, Side,
+};
+
+use crate::bytes::read_u64_be;
+
+/// Root over all chunk digests together with the chunk count and total length
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkCommitment {
+    pub root: Vec<u8>,
+    pub chunk_count: usize,
+    pub total_len: u64,
+}
+
+impl ChunkCommitment {
+    /// Encodes the chunk count (u64 BE), the total length (u64 BE), then the root
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + self.root.len());
+        bytes.extend_from_slice(&(self.chunk_count as u64).to_be_bytes());
+        bytes.extend_from_slice(&self.total_len.to_be_bytes());
+        bytes.extend_from_slice(&self.root);
+        bytes
+    }
+
+    /// Decodes a `to_bytes` encoding, such as a `"chunked_hash"` result value
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let chunk_count = usize::try_from(read_u64_be(bytes, 0)?)?;
+        let total_len = read_u64_be(bytes, 8)?;
+        Ok(Self {
+            root: bytes[16..].to_vec(),
+            chunk_count,
+            total_len,
+        })
+    }
+
+    /// Whether `chunk` is chunk `index` of the committed input
+    ///
+    /// `hasher` must be a fresh instance of the hash the input was chunked with.
+    pub fn verify_chunk(
+        &self,
+        hasher: &dyn DeterministicHasher,
+        index: usize,
+        chunk: &[u8],
+        proof: &[ProofStep],
+    ) -> bool {
+        if index >= self.chunk_count {
+            return false;
+        }
+        let sides = path_sides(index, self.chunk_count);
+        if proof.len() != sides.len()
+            || proof
+                .iter()
+                .zip(&sides)
+                .any(|(step, side)| step.side != *side)
+        {
+            return false;
+        }
+        verify_proof_with(hasher, &digest(hasher, chunk), proof, &self.root)
+    }
+}
+
+/// Incremental hasher producing a commitment after each chunk
+pub struct ChunkedHasher {
+    hasher: Box<dyn DeterministicHasher>,
+    digests: Vec<Vec<u8>>,
+    /// Roots of complete subtrees with their leaf counts, largest first
+    frontier: Vec<(usize, Vec<u8>)>,
+    total_len: u64,
+}
+
+impl ChunkedHasher {
+    /// Chunked hasher over fresh copies of `hasher`
+    pub fn new(hasher: Box<dyn DeterministicHasher>) -> Self {
+        Self {
+            hasher,
+            digests: Vec::new(),
+            frontier: Vec::new(),
+            total_len: 0,
+        }
+    }
+
+    /// Adds the next chunk and returns the root over every chunk so far
+    pub fn update(&mut self, chunk: &[u8]) -> Vec<u8> {
+        let leaf = digest(self.hasher.as_ref(), chunk);
+        self.digests.push(leaf.clone());
+        self.total_len += chunk.len() as u64;
+
+        // Merge equal-sized subtrees, like carrying in a binary counter
+        let mut node = (1, leaf);
+        while let Some((size, _)) = self.frontier.last() {
+            if *size != node.0 {
+                break;
+            }
+            let (size, left) = self.frontier.pop().unwrap();
+            node = (
+                size * 2,
+                hash_combine_with(self.hasher.as_ref(), &left, &node.1),
+            );
+        }
+        self.frontier.push(node);
+
+        self.root()
+    }
+
+    /// Ends the input, keeping the chunk digests for proof generation
+    ///
+    /// With no chunks the root is the hasher's digest of the empty input.
+    pub fn finish(self) -> ChunkedDigest {
+        let root = if self.digests.is_empty() {
+            self.hasher.clone_state().finalize()
+        } else {
+            self.root()
+        };
+        ChunkedDigest {
+            commitment: ChunkCommitment {
+                root,
+                chunk_count: self.digests.len(),
+                total_len: self.total_len,
+            },
+            hasher: self.hasher,
+            digests: self.digests,
+        }
+    }
+
+    /// Folds the frontier from the smallest subtree up
+    fn root(&self) -> Vec<u8> {
+        let mut subtrees = self.frontier.iter().rev();
+        let Some((_, smallest)) = subtrees.next() else {
+            return Vec::new();
+        };
+        subtrees.fold(smallest.clone(), |right, (_, left)| {
+            hash_combine_with(self.hasher.as_ref(), left, &right)
+        })
+    }
+}
+
+/// Final commitment plus the chunk digests needed to prove membership
+pub struct ChunkedDigest {
+    commitment: ChunkCommitment,
+    hasher: Box<dyn DeterministicHasher>,
+    digests: Vec<Vec<u8>>,
+}
+
+impl ChunkedDigest {
+    /// The commitment to publish or sign
+    pub fn commitment(&self) -> &ChunkCommitment {
+        &self.commitment
+    }
+
+    /// Inclusion proof for chunk `index`, or `None` past the last chunk
+    pub fn proof(&self, index: usize) -> Option<Vec<ProofStep>> {
+        (index < self.digests.len()).then(|| self.proof_in(&self.digests, index))
+    }
+
+    /// Proof steps from leaf `index` up to the root of `digests`
+    fn proof_in(&self, digests: &[Vec<u8>], index: usize) -> Vec<ProofStep> {
+        if digests.len() <= 1 {
+            return Vec::new();
+        }
+        let split = split_point(digests.len());
+        if index < split {
+            let mut steps = self.proof_in(&digests[..split], index);
+            steps.push(ProofStep {
+                sibling: self.subtree_root(&digests[split..]),
+                side: Side::Right,
+            });
+            steps
+        } else {
+            let mut steps = self.proof_in(&digests[split..], index - split);
+            steps.push(ProofStep {
+                sibling: self.subtree_root(&digests[..split]),
+                side: Side::Left,
+            });
+            steps
+        }
+    }
+
+    fn subtree_root(&self, digests: &[Vec<u8>]) -> Vec<u8> {
+        if digests.len() == 1 {
+            return digests[0].clone();
+        }
+        let split = split_point(digests.len());
+        let left = self.subtree_root(&digests[..split]);
+        let right = self.subtree_root(&digests[split..]);
+        hash_combine_with(self.hasher.as_ref(), &left, &right)
+    }
+}
+
+fn digest(hasher: &dyn DeterministicHasher, chunk: &[u8]) -> Vec<u8> {
+    let mut state = hasher.clone_state();
+    state.update(chunk);
+    state.finalize()
+}
+
+/// Largest power of two strictly below `count`, for `count >= 2`
+fn split_point(count: usize) -> usize {
+    1 << (usize::BITS - 1 - (count - 1).leading_zeros())
+}
+
+/// Sibling sides, leaf first, on the path to leaf `index` of `count`
+fn path_sides(index: usize, count: usize) -> Vec<Side> {
+    if count <= 1 {
+        return Vec::new();
+    }
+    let split = split_point(count);
+    let (mut sides, side) = if index < split {
+        (path_sides(index, split), Side::Right)
+    } else {
+        (path_sides(index - split, count - split), Side::Left)
+    };
+    sides.push(side);
+    sides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkCommitment;
+    use crate::tests::TEST_SIGNING_KEY;
+    use crate::DeterministicEngine;
+    use deterministic_engine::hasher::Sha256Hasher;
+
+    #[test]
+    fn test_chunk_membership_verifies_against_final_commitment() {
+        let engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let chunks: Vec<Vec<u8>> = (0..7u8).map(|i| vec![i; 100 + i as usize]).collect();
+
+        let mut hasher = engine.chunked_hasher();
+        let intermediate: Vec<Vec<u8>> = chunks.iter().map(|chunk| hasher.update(chunk)).collect();
+        let digest = hasher.finish();
+        let commitment = digest.commitment();
+        assert_eq!(commitment.chunk_count, 7);
+        assert_eq!(
+            commitment.total_len,
+            chunks.iter().map(|c| c.len() as u64).sum::<u64>()
+        );
+        assert_eq!(intermediate[6], commitment.root);
+
+        // Each intermediate commitment is the final root of that prefix
+        let mut prefix = engine.chunked_hasher();
+        prefix.update(&chunks[0]);
+        prefix.update(&chunks[1]);
+        prefix.update(&chunks[2]);
+        assert_eq!(prefix.finish().commitment().root, intermediate[2]);
+
+        let verifier = Sha256Hasher::default();
+        for (index, chunk) in chunks.iter().enumerate() {
+            let proof = digest.proof(index).unwrap();
+            assert!(commitment.verify_chunk(&verifier, index, chunk, &proof));
+        }
+
+        let proof = digest.proof(4).unwrap();
+        assert!(!commitment.verify_chunk(&verifier, 4, b"tampered", &proof));
+        assert!(!commitment.verify_chunk(&verifier, 5, &chunks[4], &proof));
+        assert!(digest.proof(7).is_none());
+    }
+
+    #[test]
+    fn test_signed_chunked_result_verifies_chunks() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let chunks: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; 64]).collect();
+
+        let mut hasher = engine.chunked_hasher();
+        for chunk in &chunks {
+            hasher.update(chunk);
+        }
+        let digest = hasher.finish();
+        let result = engine.sign_chunk_commitment(digest.commitment()).unwrap();
+        assert!(engine
+            .verify_result(&result, "chunked_hash", &result.value)
+            .unwrap());
+
+        // A verifier holding only the signed result and one chunk checks it
+        let commitment = ChunkCommitment::from_bytes(&result.value).unwrap();
+        assert_eq!(&commitment, digest.commitment());
+        let verifier = Sha256Hasher::default();
+        assert!(commitment.verify_chunk(&verifier, 3, &chunks[3], &digest.proof(3).unwrap()));
+        assert!(!commitment.verify_chunk(&verifier, 3, &chunks[2], &digest.proof(3).unwrap()));
+
+        // The count and length are signed along with the root
+        let mut shortened = result.clone();
+        shortened.value[7] = 4;
+        assert!(!engine
+            .verify_result(&shortened, "chunked_hash", &shortened.value)
+            .unwrap());
+        let mut lengthened = result.clone();
+        lengthened.value[15] ^= 1;
+        assert!(!engine
+            .verify_result(&lengthened, "chunked_hash", &lengthened.value)
+            .unwrap());
+        assert!(ChunkCommitment::from_bytes(&result.value[..12]).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+//! Timestamp sources for computation results
+//!
+//! The engine reads wall-clock time only through the `Clock` trait so that
+//! tests and reproducibility checks can pin the `timestamp` of every result.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of Unix timestamps (in seconds) for computation results
+pub trait Clock: Send + Sync {
+    /// Returns the current time as seconds since the Unix epoch
+    fn now(&self) -> u64;
+}
+
+/// Clock backed by the operating system's wall-clock time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+    }
+}
+
+/// Clock that always reports the same instant, for reproducible snapshots
+#[cfg(test)]
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub u64);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,35 @@
+//! Hash commitments to computation results
+//!
+//! A commitment binds to a result without revealing it; publishing the
+//! opening later lets anyone check the value. Hiding rests on the opening's
+//! randomness, which the engine draws from its seeded RNG, so the value stays
+//! hidden only from parties who do not know the engine seed.
+
+use sha2::{Digest, Sha256};
+
+/// Domain-separation prefix for result commitments
+const COMMITMENT_DOMAIN: &[u8] = b"DETERMINISTIC_COMMITMENT_V1";
+
+/// Published commitment to a result value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Commitment(pub [u8; 32]);
+
+/// Secret needed to open a commitment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opening {
+    pub randomness: [u8; 32],
+}
+
+/// Commit to `value` with the given opening randomness
+pub fn commit(value: &[u8], opening: &Opening) -> Commitment {
+    let mut hasher = Sha256::new();
+    hasher.update(COMMITMENT_DOMAIN);
+    hasher.update(opening.randomness);
+    hasher.update(value);
+    Commitment(hasher.finalize().into())
+}
+
+/// Check that `opening` opens `commitment` to `value`
+pub fn verify_commitment(commitment: &Commitment, value: &[u8], opening: &Opening) -> bool {
+    commit(value, opening) == *commitment
+}
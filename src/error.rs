@@ -0,0 +1,47 @@
+//! Typed errors surfaced by the computation engine
+//!
+//! `compute` still returns `Box<dyn Error>`; callers that need to react to a
+//! specific failure can downcast to `ComputeError`.
+
+use deterministic_engine::hasher::TruncationError;
+use thiserror::Error;
+
+/// Errors raised by the engine itself rather than by an operation's input parsing
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ComputeError {
+    /// The operation exists but is not enabled for this engine
+    #[error("Operation not permitted: {0}")]
+    OperationNotPermitted(String),
+
+    /// A strict seed policy rejected a low-entropy seed
+    #[error("Seed has {bits} significant bits, policy requires at least {min_bits}")]
+    WeakSeed { bits: u32, min_bits: u32 },
+
+    /// A fixed-width field extends past the end of the input
+    #[error("Read of {width} bytes at offset {offset} exceeds input length {len}")]
+    OutOfBounds {
+        offset: usize,
+        width: usize,
+        len: usize,
+    },
+
+    /// `assert_output` computed a value other than the expected one; both are hex
+    #[error("Output mismatch for {operation}: expected {expected}, got {actual}")]
+    OutputMismatch {
+        operation: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// A requested hash truncation is too short or longer than the digest
+    #[error("Invalid hash truncation: {0}")]
+    InvalidTruncation(TruncationError),
+
+    /// With cache verification on, a cached result differed from a fresh computation
+    #[error("Cached result for {operation} (key {key}) differs from a fresh computation")]
+    CacheDivergence { operation: String, key: String },
+
+    /// The operation itself failed, e.g. on malformed input
+    #[error("Computation failed: {0}")]
+    Failed(String),
+}
@@ -0,0 +1,327 @@
+//! Pluggable hash functions shared by the engine and the Merkle tree
+//!
+//! A `DeterministicHasher` is used as a prototype: callers keep one fresh
+//! instance and call `clone_state` whenever they need a new digest, so any
+//! implementation, including ones defined outside this crate, can be
+//! injected without a separate factory type.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use sha2::Digest;
+
+/// Shortest digest `TruncatedHasher` accepts, keeping 64-bit collision resistance
+pub const MIN_TRUNCATED_LEN: usize = 16;
+
+/// Incremental hash function with a fixed-length output
+pub trait DeterministicHasher: Send + Sync {
+    /// Absorbs `data` into the hash state
+    fn update(&mut self, data: &[u8]);
+
+    /// Consumes the state and returns the digest
+    fn finalize(self: Box<Self>) -> Vec<u8>;
+
+    /// Copies the current state, leaving `self` untouched
+    fn clone_state(&self) -> Box<dyn DeterministicHasher>;
+
+    /// Length in bytes of the digest returned by `finalize`
+    fn output_len(&self) -> usize;
+
+    /// Algorithm name recorded in exported parameters, e.g. `"sha256"`
+    ///
+    /// Hashers defined outside this crate report `"custom"` unless they
+    /// name themselves.
+    fn algorithm(&self) -> String {
+        String::from("custom")
+    }
+}
+
+/// SHA-256, the default for both the engine and the Merkle tree
+#[derive(Debug, Clone, Default)]
+pub struct Sha256Hasher(sha2::Sha256);
+
+/// SHA-512
+#[derive(Debug, Clone, Default)]
+pub struct Sha512Hasher(sha2::Sha512);
+
+/// BLAKE3 with its default 32-byte output
+#[derive(Debug, Clone, Default)]
+pub struct Blake3Hasher {
+    state: blake3::Hasher,
+    keyed: bool,
+}
+
+/// BLAKE3 key-derivation context for `Blake3Hasher::keyed`
+const BLAKE3_KEY_CONTEXT: &str = "deterministic-engine keyed hasher v1";
+
+/// Another hasher with its digest cut to a fixed prefix, e.g. 16-byte SHA-256
+///
+/// Inside a `MerkleTree` every leaf and interior hash is truncated, so proof
+/// siblings have the truncated length too.
+pub struct TruncatedHasher {
+    inner: Box<dyn DeterministicHasher>,
+    len: usize,
+}
+
+/// Truncation length below `MIN_TRUNCATED_LEN` or beyond the inner digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TruncationError {
+    pub len: usize,
+    pub output_len: usize,
+}
+
+impl fmt::Display for TruncationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "truncation to {} bytes outside {}..={}",
+            self.len, MIN_TRUNCATED_LEN, self.output_len
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TruncationError {}
+
+impl TruncatedHasher {
+    /// Keeps the first `len` bytes of each `inner` digest
+    pub fn new(
+        inner: impl DeterministicHasher + 'static,
+        len: usize,
+    ) -> Result<Self, TruncationError> {
+        let output_len = inner.output_len();
+        if !(MIN_TRUNCATED_LEN..=output_len).contains(&len) {
+            return Err(TruncationError { len, output_len });
+        }
+        Ok(Self {
+            inner: Box::new(inner),
+            len,
+        })
+    }
+}
+
+impl DeterministicHasher for TruncatedHasher {
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        let mut digest = self.inner.finalize();
+        digest.truncate(self.len);
+        digest
+    }
+
+    fn clone_state(&self) -> Box<dyn DeterministicHasher> {
+        Box::new(Self {
+            inner: self.inner.clone_state(),
+            len: self.len,
+        })
+    }
+
+    fn output_len(&self) -> usize {
+        self.len
+    }
+
+    fn algorithm(&self) -> String {
+        format!("{}@truncate-{}", self.inner.algorithm(), self.len)
+    }
+}
+
+impl DeterministicHasher for Sha256Hasher {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.finalize().to_vec()
+    }
+
+    fn clone_state(&self) -> Box<dyn DeterministicHasher> {
+        Box::new(self.clone())
+    }
+
+    fn output_len(&self) -> usize {
+        32
+    }
+
+    fn algorithm(&self) -> String {
+        String::from("sha256")
+    }
+}
+
+impl DeterministicHasher for Sha512Hasher {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.finalize().to_vec()
+    }
+
+    fn clone_state(&self) -> Box<dyn DeterministicHasher> {
+        Box::new(self.clone())
+    }
+
+    fn output_len(&self) -> usize {
+        64
+    }
+
+    fn algorithm(&self) -> String {
+        String::from("sha512")
+    }
+}
+
+impl Blake3Hasher {
+    /// BLAKE3 in keyed mode, with the 32-byte key derived from `key`
+    ///
+    /// Keys of any length are accepted; derivation under a fixed context
+    /// gives distinct keys distinct, unrelated hash functions.
+    pub fn keyed(key: &[u8]) -> Self {
+        Self {
+            state: blake3::Hasher::new_keyed(&blake3::derive_key(BLAKE3_KEY_CONTEXT, key)),
+            keyed: true,
+        }
+    }
+}
+
+impl DeterministicHasher for Blake3Hasher {
+    fn update(&mut self, data: &[u8]) {
+        self.state.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.state.finalize().as_bytes().to_vec()
+    }
+
+    fn clone_state(&self) -> Box<dyn DeterministicHasher> {
+        Box::new(self.clone())
+    }
+
+    fn output_len(&self) -> usize {
+        blake3::OUT_LEN
+    }
+
+    // The key is not recorded: a keyed digest cannot be checked without it
+    fn algorithm(&self) -> String {
+        String::from(if self.keyed { "blake3-keyed" } else { "blake3" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verifier::merkle::tests::numbered_dependencies;
+    use crate::verifier::merkle::{Dependency, MerkleTree};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn test_blake3_merkle_root_matches_manual_derivation() {
+        let dependencies = vec![
+            Dependency {
+                name: "left-pad".to_string(),
+                version: "1.3.0".to_string(),
+                integrity: None,
+                resolved: None,
+                marker: None,
+            },
+            Dependency {
+                name: "semver".to_string(),
+                version: "6.3.1".to_string(),
+                integrity: None,
+                resolved: None,
+                marker: None,
+            },
+        ];
+        let mut tree = MerkleTree::new().with_hasher(Blake3Hasher::default());
+        tree.build_from_dependencies(dependencies.clone()).unwrap();
+
+        // Leaf encoding: length-prefixed name and version, absent-marker bytes
+        let leaf = |dep: &Dependency| {
+            let mut hasher = blake3::Hasher::new();
+            for field in [dep.name.as_bytes(), dep.version.as_bytes()] {
+                hasher.update(&(field.len() as u64).to_be_bytes());
+                hasher.update(field);
+            }
+            hasher.update(&[0, 0]);
+            *hasher.finalize().as_bytes()
+        };
+        let mut root = blake3::Hasher::new();
+        root.update(&leaf(&dependencies[0]));
+        root.update(&leaf(&dependencies[1]));
+        assert_eq!(
+            tree.root_hash().unwrap(),
+            root.finalize().as_bytes().to_vec()
+        );
+
+        // Proofs generated under BLAKE3 verify under BLAKE3 only
+        let proof = tree.generate_proof(&dependencies[1]).unwrap();
+        assert!(tree.verify_inclusion(&dependencies[1], &proof));
+        let mut sha_tree = MerkleTree::new();
+        sha_tree
+            .build_from_dependencies(dependencies.clone())
+            .unwrap();
+        assert_ne!(sha_tree.root_hash(), tree.root_hash());
+        assert!(!sha_tree.verify_inclusion(&dependencies[1], &proof));
+    }
+
+    #[test]
+    fn test_output_len_matches_digest() {
+        let hashers: [Box<dyn DeterministicHasher>; 3] = [
+            Box::new(Sha256Hasher::default()),
+            Box::new(Sha512Hasher::default()),
+            Box::new(Blake3Hasher::default()),
+        ];
+        for hasher in hashers {
+            let expected = hasher.output_len();
+            let mut state = hasher.clone_state();
+            state.update(b"abc");
+            assert_eq!(state.finalize().len(), expected);
+        }
+    }
+
+    #[test]
+    fn test_algorithm_names_distinguish_hashers() {
+        assert_eq!(Sha256Hasher::default().algorithm(), "sha256");
+        assert_eq!(Sha512Hasher::default().algorithm(), "sha512");
+        assert_eq!(Blake3Hasher::default().algorithm(), "blake3");
+        assert_eq!(Blake3Hasher::keyed(b"tenant-a").algorithm(), "blake3-keyed");
+        assert_eq!(
+            TruncatedHasher::new(Sha256Hasher::default(), 16)
+                .unwrap()
+                .algorithm(),
+            "sha256@truncate-16"
+        );
+    }
+
+    #[test]
+    fn test_truncated_merkle_tree_uses_digest_prefixes() {
+        let dependencies = numbered_dependencies(4);
+        let mut tree = MerkleTree::new()
+            .with_hasher(TruncatedHasher::new(Sha256Hasher::default(), 16).unwrap());
+        tree.build_from_dependencies(dependencies.clone()).unwrap();
+        assert_eq!(tree.root_hash().unwrap().len(), 16);
+
+        let proof = tree.generate_proof(&dependencies[2]).unwrap();
+        assert!(proof.iter().all(|step| step.sibling.len() == 16));
+        assert!(tree.verify_inclusion(&dependencies[2], &proof));
+
+        let mut full = Sha256Hasher::default();
+        full.update(b"abc");
+        let mut short = TruncatedHasher::new(Sha256Hasher::default(), 16).unwrap();
+        short.update(b"abc");
+        assert_eq!(Box::new(short).finalize(), Box::new(full).finalize()[..16]);
+
+        assert_eq!(
+            TruncatedHasher::new(Sha256Hasher::default(), 8).err(),
+            Some(TruncationError {
+                len: 8,
+                output_len: 32
+            })
+        );
+        assert!(TruncatedHasher::new(Sha256Hasher::default(), 33).is_err());
+    }
+}
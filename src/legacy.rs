@@ -0,0 +1,78 @@
+//! Verification of results produced under earlier proof schemes
+//!
+//! Version 1 proofs hashed the domain tag, operation, input and result back
+//! to back, which let different (operation, input) splits collide. Version 2
+//! length-prefixes each field. Stored v1 results stay checkable here, so an
+//! upgrade does not strand historical artifacts.
+
+use sha2::{Digest, Sha256};
+
+use crate::crypto::DeterministicCrypto;
+use crate::ComputationResult;
+
+/// Domain tag used by every v1 proof unless a deployment overrode it
+pub const V1_PROOF_DOMAIN_TAG: &[u8] = b"DETERMINISTIC_PROOF_V1";
+
+/// Derive a v1 proof hash: SHA-256(tag || operation || input || result || seed)
+pub fn proof_v1(
+    domain_tag: &[u8],
+    operation: &str,
+    input: &[u8],
+    result: &[u8],
+    seed: u64,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(domain_tag);
+    hasher.update(operation.as_bytes());
+    hasher.update(input);
+    hasher.update(result);
+    hasher.update(seed.to_be_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Checks results whose proofs use the v1 scheme
+pub struct LegacyVerifier {
+    seed: u64,
+    crypto: DeterministicCrypto,
+    domain_tag: Vec<u8>,
+}
+
+impl LegacyVerifier {
+    /// Verifier for results produced by an engine with `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            crypto: DeterministicCrypto::new(seed),
+            domain_tag: V1_PROOF_DOMAIN_TAG.to_vec(),
+        }
+    }
+
+    /// Use the domain tag the producing deployment was configured with
+    pub fn with_domain_tag(mut self, tag: impl Into<Vec<u8>>) -> Self {
+        self.domain_tag = tag.into();
+        self
+    }
+
+    /// Re-derive the v1 proof and check it, the signature and the seed
+    pub fn verify(
+        &self,
+        result: &ComputationResult,
+        operation: &str,
+        input: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let expected_proof = proof_v1(&self.domain_tag, operation, input, &result.value, self.seed);
+        if expected_proof != result.proof_hash {
+            return Ok(false);
+        }
+
+        if !self.crypto.verify_signature(
+            &result.value,
+            &result.proof_hash,
+            &result.verification_signature.to_hex(),
+        )? {
+            return Ok(false);
+        }
+
+        Ok(result.seed == self.seed)
+    }
+}
@@ -1,3 +1,15 @@
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+extern crate alloc;
+
+pub mod commitment;
+pub mod hasher;
+#[cfg(feature = "std")]
+pub mod result;
+#[cfg(feature = "std")]
+pub mod store;
+pub mod verifier;
+
 /// Verifies deterministic build given a lockfile.
 pub fn verify_build(_lockfile: &str) -> bool {
     // Stub always returns true for demo.
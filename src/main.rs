@@ -5,32 +5,177 @@
 //! Copyright (c) 2025 Vaibhav Kumar
 //! MIT License
 
-use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
-use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
+use data_encoding::{BASE32, BASE64};
+use num_bigint::BigUint;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+#[cfg(feature = "async")]
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use unicode_normalization::UnicodeNormalization;
 
+mod audit;
+mod audit_diff;
+mod benchmarks;
+mod bytes;
+mod chunked;
+mod clock;
+mod cpu_time;
 mod crypto;
+mod error;
+mod legacy;
+mod report;
+mod schema;
+mod self_test;
 mod verification;
-mod benchmarks;
-mod audit;
 
+use audit::AuditLogger;
+use benchmarks::PerformanceBenchmark;
+use bytes::{read_f64_be, read_u32_be, read_u64_be, write_u64_be};
+use chunked::{ChunkCommitment, ChunkedHasher};
+use clock::{Clock, SystemClock};
 use crypto::DeterministicCrypto;
+use deterministic_engine::commitment::{self, Commitment, Opening};
+use deterministic_engine::hasher::{
+    DeterministicHasher, Sha256Hasher, TruncationError, MIN_TRUNCATED_LEN,
+};
+use deterministic_engine::result::{
+    proof_operation, proof_v2, ComputationResult, ReferenceAttestation, DEFAULT_PROOF_DOMAIN_TAG,
+    PROOF_HASH, PROOF_VERSION,
+};
+use deterministic_engine::verifier::attestation::{verify_signed_result, RootSigningKey};
+use deterministic_engine::verifier::engine::VerificationParams;
+use error::ComputeError;
+use legacy::LegacyVerifier;
+use report::{Reporter, Symbol};
 use verification::FormalVerifier;
-use benchmarks::PerformanceBenchmark;
-use audit::AuditLogger;
 
-/// Core deterministic computation result with cryptographic proof
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ComputationResult {
-    pub value: Vec<u8>,
-    pub proof_hash: String,
-    pub timestamp: u64,
-    pub seed: u64,
-    pub verification_signature: String,
-    pub execution_time_ns: u64,
+/// Policy for screening seeds before an engine is constructed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeedPolicy {
+    /// Accept any seed (the historical behaviour)
+    #[default]
+    Permissive,
+    /// Reject seeds whose significant bit length is below `min_bits`
+    ///
+    /// Bit length is only a proxy for entropy: it catches zero, small integers
+    /// and other accidentally predictable seeds, not every weak choice.
+    Strict { min_bits: u32 },
+}
+
+impl SeedPolicy {
+    /// Strict policy with the recommended 32-bit minimum
+    pub const fn strict() -> Self {
+        Self::Strict { min_bits: 32 }
+    }
+}
+
+/// Up-front cost estimate for an operation, produced without computing it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CostEstimate {
+    /// Exact output length in bytes, or `None` when it depends on the crypto backend
+    /// or on the input's value rather than its header
+    pub output_size: Option<usize>,
+    /// Asymptotic complexity in terms of the input's header fields
+    pub complexity: &'static str,
+    /// Rough count of elementary steps, for comparing jobs against a budget
+    pub work_units: u64,
+}
+
+/// Operations handled by `compute`, in dispatch order
+pub const BUILTIN_OPERATIONS: &[&str] = &[
+    "hash",
+    "encrypt",
+    "sign",
+    "matrix_multiply",
+    "matrix_lu",
+    "dot_product",
+    "normalize_vector",
+    "moving_average",
+    "kmeans_assign",
+    "polynomial_eval",
+    "fibonacci",
+    "fibonacci_sequence",
+    "factorize",
+    "sort",
+    "shuffle",
+    "base_convert",
+    "sample",
+    "stratified_sample",
+    "top_k",
+    "rolling_hash",
+    "set_union",
+    "set_intersection",
+    "merge_sorted",
+    "histogram",
+    "quantile",
+    "normalize_utf8",
+    "crc32",
+    "crc64",
+    "base64_encode",
+    "base64_decode",
+    "base32_encode",
+    "base32_decode",
+    "rle_encode",
+    "rle_decode",
+    "byte_diff",
+    "byte_patch",
+    "attest_reference",
+];
+
+/// Operations that run through the crypto backend's mutable state
+///
+/// Cache verification does not recompute hits for these: a recomputation
+/// would advance that state, and could disagree with the cached value
+/// without anything being wrong.
+const STATEFUL_OPERATIONS: &[&str] = &["encrypt", "sign"];
+
+/// Largest value `factorize` accepts, keeping trial division under 2^20 steps
+const MAX_FACTORIZE_INPUT: u64 = 1 << 40;
+
+/// Largest LCS table `byte_diff` builds, bounding its memory to 16 MiB
+const MAX_DIFF_CELLS: usize = 1 << 22;
+
+/// `byte_diff` op tags, each followed by a u32 BE length
+const DIFF_COPY: u8 = 0;
+const DIFF_DELETE: u8 = 1;
+/// Followed by the length and then the inserted bytes
+const DIFF_INSERT: u8 = 2;
+
+/// Largest bucket count `histogram` accepts, bounding its output size
+const MAX_HISTOGRAM_BUCKETS: usize = 1 << 16;
+
+/// CRC-32 as used by zlib, PNG and Ethernet (CRC-32/ISO-HDLC)
+const CRC32: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+/// CRC-64 as used by xz (CRC-64/XZ, the reflected ECMA-182 polynomial)
+const CRC64: crc::Crc<u64> = crc::Crc::<u64>::new(&crc::CRC_64_XZ);
+
+/// Which merge `set_operation` performs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetOperation {
+    Union,
+    Intersection,
+    /// Union that keeps every copy of repeated values
+    Merge,
+}
+
+/// Bytes covered by each `rolling_hash` window
+const ROLLING_HASH_WINDOW: usize = 48;
+
+/// Odd multiplier of the `rolling_hash` polynomial (the 64-bit FNV prime)
+const ROLLING_HASH_BASE: u64 = 0x0000_0100_0000_01B3;
+
+/// Cached result together with the operation that produced it
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    operation: String,
+    result: ComputationResult,
 }
 
 /// Deterministic computation engine with formal verification
@@ -38,175 +183,1252 @@ pub struct DeterministicEngine {
     seed: u64,
     rng: ChaCha20Rng,
     crypto: DeterministicCrypto,
+    /// Ed25519 key results are signed with, independent of the seed
+    signing_key: RootSigningKey,
     verifier: FormalVerifier,
     audit_logger: AuditLogger,
-    computation_cache: HashMap<String, ComputationResult>,
+    computation_cache: HashMap<String, CacheEntry>,
+    /// Cache keys in insertion order, oldest first, for eviction
+    cache_order: VecDeque<String>,
+    /// Sum of `heap_size` over cached results
+    cache_bytes: usize,
+    max_cache_bytes: Option<usize>,
+    clock: Box<dyn Clock>,
+    allowed_operations: Option<HashSet<String>>,
+    measure_cpu_time: bool,
+    cache_enabled: bool,
+    verify_cache_hits: bool,
+    domain_tag: Vec<u8>,
+    /// Prototype for the `"hash"` operation; proofs and cache keys stay SHA-256
+    hasher: Box<dyn DeterministicHasher>,
+    /// Byte length `"hash"` digests are cut to, recorded in their proofs
+    hash_truncation: Option<usize>,
 }
 
 impl DeterministicEngine {
-    /// Initialize engine with cryptographic seed
-    pub fn new(seed: Option<u64>) -> Self {
-        let actual_seed = seed.unwrap_or_else(|| {
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("Time went backwards")
-                .as_secs()
-        });
-        
+    /// Initialize engine with cryptographic seed and result-signing secret
+    ///
+    /// `signing_key` is the 32-byte Ed25519 secret results are signed with.
+    /// Every result carries its seed, so the key must come from elsewhere;
+    /// verifiers check signatures against its public half, as exported by
+    /// `export_verification_params`. Without a seed, a full 64-bit one is
+    /// drawn from the OS-seeded thread RNG.
+    pub fn new(seed: Option<u64>, signing_key: &[u8; 32]) -> Self {
+        let actual_seed = seed.unwrap_or_else(rand::random);
+
         let rng = ChaCha20Rng::seed_from_u64(actual_seed);
-        
+
         Self {
             seed: actual_seed,
             rng,
             crypto: DeterministicCrypto::new(actual_seed),
+            signing_key: RootSigningKey::from_seed(signing_key),
             verifier: FormalVerifier::new(),
             audit_logger: AuditLogger::new(),
             computation_cache: HashMap::new(),
+            cache_order: VecDeque::new(),
+            cache_bytes: 0,
+            max_cache_bytes: None,
+            clock: Box::new(SystemClock),
+            allowed_operations: None,
+            measure_cpu_time: false,
+            cache_enabled: true,
+            verify_cache_hits: false,
+            domain_tag: DEFAULT_PROOF_DOMAIN_TAG.to_vec(),
+            hasher: Box::new(Sha256Hasher::default()),
+            hash_truncation: None,
+        }
+    }
+
+    /// Initialize engine after screening the seed against `policy`
+    pub fn try_new(
+        seed: Option<u64>,
+        signing_key: &[u8; 32],
+        policy: SeedPolicy,
+    ) -> Result<Self, ComputeError> {
+        let engine = Self::new(seed, signing_key);
+        if let SeedPolicy::Strict { min_bits } = policy {
+            let bits = u64::BITS - engine.seed.leading_zeros();
+            if bits < min_bits {
+                return Err(ComputeError::WeakSeed { bits, min_bits });
+            }
+        }
+        Ok(engine)
+    }
+
+    /// Replace the timestamp source used for computation results
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Restrict `compute` to the given operations; all operations are allowed by default
+    pub fn with_allowed_operations(mut self, operations: &[&str]) -> Self {
+        self.allowed_operations = Some(operations.iter().map(|op| op.to_string()).collect());
+        self
+    }
+
+    /// Additionally record thread CPU time for each computation
+    pub fn with_cpu_time_measurement(mut self, enabled: bool) -> Self {
+        self.measure_cpu_time = enabled;
+        self
+    }
+
+    /// Replace the proof domain-separation tag
+    ///
+    /// Deployments with distinct tags reject each other's proofs, so a result
+    /// from one cannot be replayed as valid in another.
+    pub fn with_domain_tag(mut self, tag: impl Into<Vec<u8>>) -> Self {
+        self.domain_tag = tag.into();
+        self
+    }
+
+    /// Replace the hash function behind the `"hash"` operation (SHA-256 by default)
+    pub fn with_hasher(mut self, hasher: impl DeterministicHasher + 'static) -> Self {
+        self.hasher = Box::new(hasher);
+        self
+    }
+
+    /// Cut `"hash"` digests to their first `len` bytes
+    ///
+    /// Lengths below `MIN_TRUNCATED_LEN` or beyond the hasher's output are
+    /// rejected, so call this after `with_hasher`. Proofs for truncated
+    /// digests are taken over the operation `"hash@truncate-<len>"`, so a
+    /// truncated result never verifies as a full one or at another length.
+    pub fn with_hash_truncation(mut self, len: usize) -> Result<Self, ComputeError> {
+        let output_len = self.hasher.output_len();
+        if !(MIN_TRUNCATED_LEN..=output_len).contains(&len) {
+            return Err(ComputeError::InvalidTruncation(TruncationError {
+                len,
+                output_len,
+            }));
+        }
+        self.hash_truncation = Some(len);
+        Ok(self)
+    }
+
+    /// Turn result caching on or off; it is on by default
+    ///
+    /// With caching off every call recomputes and nothing is stored, so call
+    /// timing cannot reveal whether an input was seen before. Computations are
+    /// still audit-logged. Disabling also drops anything already cached.
+    pub fn with_caching(mut self, enabled: bool) -> Self {
+        self.cache_enabled = enabled;
+        if !enabled {
+            self.computation_cache.clear();
+            self.cache_order.clear();
+            self.cache_bytes = 0;
+        }
+        self
+    }
+
+    /// Recompute every cache hit and compare it with the cached result
+    ///
+    /// The cache then costs a full computation per hit and acts as a check on
+    /// memory corruption or nondeterminism instead of a speedup; a hit that
+    /// disagrees is an error (`ComputeError::CacheDivergence`). Hits for
+    /// `encrypt` and `sign` are served unchecked, since recomputing them would
+    /// change the crypto backend's state. Off by default.
+    pub fn with_cache_verification(mut self, enabled: bool) -> Self {
+        self.verify_cache_hits = enabled;
+        self
+    }
+
+    /// Cap the heap bytes held by cached results, evicting oldest entries first
+    ///
+    /// A result larger than the whole budget is returned but not cached.
+    pub fn with_cache_byte_limit(mut self, max_bytes: usize) -> Self {
+        self.max_cache_bytes = Some(max_bytes);
+        self.evict_to_byte_limit();
+        self
+    }
+
+    /// Check whether an operation is enabled for this engine
+    pub fn is_operation_permitted(&self, operation: &str) -> bool {
+        self.allowed_operations
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(operation))
+    }
+
+    /// Operations this engine will run, after applying any allow-list
+    pub fn supported_operations(&self) -> Vec<&str> {
+        BUILTIN_OPERATIONS
+            .iter()
+            .copied()
+            .filter(|operation| self.is_operation_permitted(operation))
+            .collect()
+    }
+
+    /// Chunked hasher over this engine's `"hash"` algorithm
+    ///
+    /// Chunks are hashed without the seed, so anyone holding the data and the
+    /// algorithm can check a chunk against the commitment. Sign the final
+    /// commitment with `sign_chunk_commitment`.
+    pub fn chunked_hasher(&self) -> ChunkedHasher {
+        ChunkedHasher::new(self.hasher.clone_state())
+    }
+
+    /// Sign a chunked hash commitment as a `"chunked_hash"` result
+    ///
+    /// The value is `commitment.to_bytes()`, which is also the proof input,
+    /// so the root, chunk count and total length are all covered by the
+    /// signature. Check it with `verify_result(result, "chunked_hash",
+    /// &result.value)`, then verify chunks against
+    /// `ChunkCommitment::from_bytes(&result.value)`. Results are not cached.
+    pub fn sign_chunk_commitment(
+        &mut self,
+        commitment: &ChunkCommitment,
+    ) -> Result<ComputationResult, Box<dyn std::error::Error>> {
+        let start_time = std::time::Instant::now();
+
+        if !self.is_operation_permitted("hash") {
+            return Err(ComputeError::OperationNotPermitted("hash".to_string()).into());
+        }
+        let result_bytes = commitment.to_bytes();
+        let proof_hash = self.generate_proof(&result_bytes, "chunked_hash", &result_bytes, &[]);
+        let verification_signature = self.signing_key.sign_result(proof_hash.as_bytes());
+        let execution_time = start_time.elapsed().as_nanos() as u64;
+
+        self.audit_logger
+            .log_computation(&proof_hash, "chunked_hash", execution_time);
+        Ok(ComputationResult {
+            value: result_bytes,
+            proof_hash,
+            timestamp: self.clock.now(),
+            seed: self.seed,
+            verification_signature,
+            execution_time_ns: execution_time,
+            cpu_time_ns: None,
+        })
+    }
+
+    /// Parameters a third party needs to verify this engine's results
+    ///
+    /// Only the public half of the signing key is exported, never the seed.
+    pub fn export_verification_params(&self) -> VerificationParams {
+        VerificationParams {
+            proof_version: PROOF_VERSION,
+            proof_hash: PROOF_HASH.to_string(),
+            hash_algorithm: self.hasher.algorithm(),
+            domain_tag: hex::encode(&self.domain_tag),
+            public_key: hex::encode(self.signing_key.public_key()),
+            hash_truncation: self.hash_truncation,
+        }
+    }
+
+    /// Hex SHA-256 over every setting that affects result bytes and proofs
+    ///
+    /// Covers the seed, the wire byte order, the `"hash"` algorithm and
+    /// truncation, the proof scheme, the domain tag and the signing public
+    /// key, each length-prefixed. The hash algorithm is identified by its
+    /// digest of the empty input, so custom hashers are distinguished without
+    /// naming them. Engines with equal fingerprints produce identical results,
+    /// signatures included, for identical inputs; caching, clocks and
+    /// allow-lists do not change results and are left out.
+    pub fn config_fingerprint(&self) -> String {
+        let hasher_probe = self.hasher.clone_state().finalize();
+        let seed = self.seed.to_be_bytes();
+        let truncation = (self.hash_truncation.unwrap_or(0) as u64).to_be_bytes();
+        let mut fingerprint = Sha256::new();
+        let fields = [
+            &b"DETERMINISTIC_CONFIG_V1"[..],
+            &seed,
+            b"big-endian",
+            &hasher_probe,
+            &truncation,
+            b"proof-v2",
+            &self.domain_tag,
+            self.signing_key.public_key(),
+        ];
+        for field in fields {
+            fingerprint.update((field.len() as u64).to_be_bytes());
+            fingerprint.update(field);
         }
+        hex::encode(fingerprint.finalize())
     }
-    
+
     /// Perform deterministic computation with formal verification
-    pub fn compute(&mut self, operation: &str, input: &[u8]) -> Result<ComputationResult, Box<dyn std::error::Error>> {
+    pub fn compute(
+        &mut self,
+        operation: &str,
+        input: &[u8],
+    ) -> Result<ComputationResult, Box<dyn std::error::Error>> {
+        self.compute_with_context(operation, input, &[])
+    }
+
+    /// `compute` scoped to an external context such as a request or tenant id
+    ///
+    /// The context is folded into the cache key and the proof, so identical
+    /// computations under different contexts neither share a cache entry nor
+    /// verify against each other. An empty context is exactly `compute`.
+    pub fn compute_with_context(
+        &mut self,
+        operation: &str,
+        input: &[u8],
+        context: &[u8],
+    ) -> Result<ComputationResult, Box<dyn std::error::Error>> {
         let start_time = std::time::Instant::now();
-        
+        let _span = tracing::debug_span!("compute", operation).entered();
+
+        if !self.is_operation_permitted(operation) {
+            return Err(ComputeError::OperationNotPermitted(operation.to_string()).into());
+        }
+
         // Generate deterministic computation key
-        let computation_key = self.generate_computation_key(operation, input);
-        
+        let computation_key = self.generate_computation_key(operation, input, context);
+
         // Check cache first
-        if let Some(cached_result) = self.computation_cache.get(&computation_key) {
+        if let Some(cached) = self
+            .computation_cache
+            .get(&computation_key)
+            .filter(|_| self.cache_enabled)
+        {
+            tracing::debug!(operation, key = %computation_key, "cache hit");
+            let cached = cached.result.clone();
+            if self.verify_cache_hits && !STATEFUL_OPERATIONS.contains(&operation) {
+                self.check_cache_hit(&cached, &computation_key, operation, input, context)?;
+            }
             self.audit_logger.log_cache_hit(&computation_key);
-            return Ok(cached_result.clone());
+            return Ok(cached);
         }
-        
+        tracing::debug!(operation, key = %computation_key, "cache miss");
+
+        let cpu_start = if self.measure_cpu_time {
+            cpu_time::thread_cpu_time_ns()
+        } else {
+            None
+        };
+
         // Perform the actual computation
+        let result_bytes = self.run_operation(operation, input)?;
+
+        let execution_time = start_time.elapsed().as_nanos() as u64;
+        let cpu_time_ns = cpu_start
+            .and_then(|start| cpu_time::thread_cpu_time_ns().map(|end| end.saturating_sub(start)));
+
+        // Generate cryptographic proof
+        let proof_hash = self.generate_proof(&result_bytes, operation, input, context);
+
+        // Create verification signature
+        let verification_signature = self.signing_key.sign_result(proof_hash.as_bytes());
+
+        // Formal verification
+        self.verifier
+            .verify_computation(operation, input, &result_bytes)?;
+
+        let result = ComputationResult {
+            value: result_bytes,
+            proof_hash,
+            timestamp: self.clock.now(),
+            seed: self.seed,
+            verification_signature,
+            execution_time_ns: execution_time,
+            cpu_time_ns,
+        };
+
+        // Cache and log
+        if self.cache_enabled {
+            self.insert_cached(
+                computation_key.clone(),
+                CacheEntry {
+                    operation: operation.to_string(),
+                    result: result.clone(),
+                },
+            );
+        }
+        self.audit_logger
+            .log_computation(&computation_key, operation, execution_time);
+
+        Ok(result)
+    }
+
+    /// Recompute a cache hit and fail if the cached value or proof disagrees
+    ///
+    /// A divergent entry is evicted, so the next call computes afresh.
+    fn check_cache_hit(
+        &mut self,
+        cached: &ComputationResult,
+        key: &str,
+        operation: &str,
+        input: &[u8],
+        context: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let fresh = self.run_operation(operation, input)?;
+        if fresh == cached.value
+            && self.generate_proof(&fresh, operation, input, context) == cached.proof_hash
+        {
+            return Ok(());
+        }
+
+        tracing::warn!(operation, key, "cached result diverged from recomputation");
+        if let Some(entry) = self.computation_cache.remove(key) {
+            self.cache_bytes -= entry.result.heap_size();
+            self.cache_order.retain(|queued| queued != key);
+        }
+        Err(ComputeError::CacheDivergence {
+            operation: operation.to_string(),
+            key: key.to_string(),
+        }
+        .into())
+    }
+
+    /// Dispatch a built-in operation, returning only its output bytes
+    fn run_operation(
+        &mut self,
+        operation: &str,
+        input: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         let result_bytes = match operation {
             "hash" => self.deterministic_hash(input),
             "encrypt" => self.deterministic_encrypt(input)?,
             "sign" => self.deterministic_sign(input)?,
             "matrix_multiply" => self.matrix_computation(input)?,
+            "matrix_lu" => self.lu_decomposition(input)?,
+            "dot_product" => self.dot_product(input)?,
+            "normalize_vector" => self.normalize_vector(input)?,
+            "moving_average" => self.moving_average(input)?,
+            "kmeans_assign" => self.kmeans_assign(input)?,
             "polynomial_eval" => self.polynomial_evaluation(input)?,
             "fibonacci" => self.fibonacci_computation(input)?,
+            "fibonacci_sequence" => self.fibonacci_sequence(input)?,
+            "factorize" => self.factorize(input)?,
+            "sort" => self.sort_records(input)?,
+            "shuffle" => self.shuffle_values(input)?,
+            "base_convert" => self.base_convert(input)?,
+            "sample" => self.sample_values(input)?,
+            "stratified_sample" => self.stratified_sample(input)?,
+            "top_k" => self.top_k(input)?,
+            "rolling_hash" => self.rolling_hash_boundaries(input)?,
+            "set_union" => self.set_operation(input, SetOperation::Union)?,
+            "set_intersection" => self.set_operation(input, SetOperation::Intersection)?,
+            "merge_sorted" => self.set_operation(input, SetOperation::Merge)?,
+            "histogram" => self.histogram(input)?,
+            "quantile" => self.quantile(input)?,
+            "normalize_utf8" => self.normalize_utf8(input)?,
+            "crc32" => self.seeded_crc32(input),
+            "crc64" => self.seeded_crc64(input),
+            "base64_encode" => BASE64.encode(input).into_bytes(),
+            "base64_decode" => self.decode_text(input, &BASE64, "Base64")?,
+            "base32_encode" => BASE32.encode(input).into_bytes(),
+            "base32_decode" => self.decode_text(input, &BASE32, "Base32")?,
+            "rle_encode" => self.rle_encode(input),
+            "rle_decode" => self.rle_decode(input)?,
+            "byte_diff" => self.byte_diff(input)?,
+            "byte_patch" => self.byte_patch(input)?,
+            "attest_reference" => self.attest_reference(input)?,
             _ => return Err(format!("Unknown operation: {}", operation).into()),
         };
-        
+        Ok(result_bytes)
+    }
+
+    /// Compute and require the result to equal `expected`
+    ///
+    /// The returned result's proof and signature cover the value, which is
+    /// exactly `expected`, so it attests that the equality held. Failures of
+    /// the computation itself are reported as `ComputeError::Failed` unless
+    /// they already are a `ComputeError`.
+    pub fn assert_output(
+        &mut self,
+        operation: &str,
+        input: &[u8],
+        expected: &[u8],
+    ) -> Result<ComputationResult, ComputeError> {
+        let result =
+            self.compute(operation, input)
+                .map_err(|e| match e.downcast::<ComputeError>() {
+                    Ok(compute_error) => *compute_error,
+                    Err(other) => ComputeError::Failed(other.to_string()),
+                })?;
+
+        if result.value != expected {
+            return Err(ComputeError::OutputMismatch {
+                operation: operation.to_string(),
+                expected: hex::encode(expected),
+                actual: hex::encode(&result.value),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Run a digest-only operation over a caller-supplied SHA-256 digest
+    ///
+    /// Only `sign` is supported, as the one operation meaningful over a
+    /// digest alone. The result is the operation applied to the 32 digest
+    /// bytes, so a large input never has to be re-read. The proof records the
+    /// pre-digest path: it is taken over `"<operation>@sha256-digest"` with
+    /// the digest and `original_len` as input, so it can never be confused
+    /// with a full-input proof. Check it with `verify_digest_result`. Results
+    /// are not cached.
+    pub fn compute_over_digest(
+        &mut self,
+        operation: &str,
+        input_digest: &[u8; 32],
+        original_len: u64,
+    ) -> Result<ComputationResult, Box<dyn std::error::Error>> {
+        let start_time = std::time::Instant::now();
+
+        if !self.is_operation_permitted(operation) {
+            return Err(ComputeError::OperationNotPermitted(operation.to_string()).into());
+        }
+        let result_bytes = match operation {
+            "sign" => self.deterministic_sign(input_digest)?,
+            _ => return Err(format!("Operation {} cannot run over a digest", operation).into()),
+        };
         let execution_time = start_time.elapsed().as_nanos() as u64;
-        
-        // Generate cryptographic proof
-        let proof_hash = self.generate_proof(&result_bytes, operation, input);
-        
-        // Create verification signature
-        let verification_signature = self.crypto.sign_result(&result_bytes, &proof_hash)?;
-        
-        // Formal verification
-        self.verifier.verify_computation(operation, input, &result_bytes)?;
-        
-        let result = ComputationResult {
+
+        let (proof_operation, proof_input) =
+            pre_digest_proof_fields(operation, input_digest, original_len);
+        let proof_hash = self.generate_proof(&result_bytes, &proof_operation, &proof_input, &[]);
+        let verification_signature = self.signing_key.sign_result(proof_hash.as_bytes());
+        self.verifier
+            .verify_computation(operation, input_digest, &result_bytes)?;
+
+        self.audit_logger
+            .log_computation(&proof_hash, &proof_operation, execution_time);
+        Ok(ComputationResult {
             value: result_bytes,
             proof_hash,
-            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            timestamp: self.clock.now(),
             seed: self.seed,
             verification_signature,
             execution_time_ns: execution_time,
+            cpu_time_ns: None,
+        })
+    }
+
+    /// Run `compute` on tokio's blocking pool so async callers don't stall
+    ///
+    /// The engine is shared behind a mutex, so concurrent calls against one
+    /// engine still execute one at a time. Errors are flattened to their
+    /// message because the boxed error from `compute` is not `Send`.
+    #[cfg(feature = "async")]
+    pub async fn compute_async(
+        engine: Arc<Mutex<Self>>,
+        operation: String,
+        input: Vec<u8>,
+    ) -> Result<ComputationResult, Box<dyn std::error::Error + Send + Sync>> {
+        tokio::task::spawn_blocking(
+            move || -> Result<_, Box<dyn std::error::Error + Send + Sync>> {
+                let mut engine = engine.lock().map_err(|_| "Engine mutex poisoned")?;
+                engine
+                    .compute(&operation, &input)
+                    .map_err(|e| e.to_string().into())
+            },
+        )
+        .await?
+    }
+
+    /// Run one operation under each seed in turn, returning every result
+    ///
+    /// The engine is temporarily reseeded for each run and restored afterwards,
+    /// including its RNG state. Each result carries, and verifies under, the
+    /// seed that produced it. Sweep results stay cached under their own seeds.
+    pub fn compute_seed_sweep(
+        &mut self,
+        operation: &str,
+        input: &[u8],
+        seeds: &[u64],
+    ) -> Result<Vec<ComputationResult>, Box<dyn std::error::Error>> {
+        let saved_seed = self.seed;
+        let saved_rng = self.rng.clone();
+        let saved_crypto =
+            std::mem::replace(&mut self.crypto, DeterministicCrypto::new(saved_seed));
+
+        let results = seeds
+            .iter()
+            .map(|&seed| {
+                self.seed = seed;
+                self.rng = ChaCha20Rng::seed_from_u64(seed);
+                self.crypto = DeterministicCrypto::new(seed);
+                self.compute(operation, input)
+            })
+            .collect();
+
+        self.seed = saved_seed;
+        self.rng = saved_rng;
+        self.crypto = saved_crypto;
+        results
+    }
+
+    /// Estimate output size and work for an operation without running it
+    ///
+    /// Only the input header is parsed, so callers can reject or queue
+    /// expensive jobs before committing to them.
+    pub fn estimate_cost(
+        &self,
+        operation: &str,
+        input: &[u8],
+    ) -> Result<CostEstimate, Box<dyn std::error::Error>> {
+        if !self.is_operation_permitted(operation) {
+            return Err(ComputeError::OperationNotPermitted(operation.to_string()).into());
+        }
+
+        let len = input.len() as u64;
+        let estimate = match operation {
+            "hash" => {
+                let output_len = self.hasher.output_len();
+                CostEstimate {
+                    output_size: Some(
+                        self.hash_truncation
+                            .map_or(output_len, |len| len.min(output_len)),
+                    ),
+                    complexity: "O(n)",
+                    work_units: len,
+                }
+            }
+            "encrypt" | "sign" => CostEstimate {
+                output_size: None,
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "matrix_multiply" => {
+                let rows = read_u32_be(input, 0)? as u64;
+                let cols = read_u32_be(input, 4)? as u64;
+                let cells = rows.checked_mul(cols).ok_or("Matrix dimensions overflow")?;
+                let output_size = cells
+                    .checked_mul(8)
+                    .and_then(|bytes| bytes.checked_add(8))
+                    .and_then(|bytes| usize::try_from(bytes).ok());
+                CostEstimate {
+                    output_size,
+                    complexity: "O(rows*cols)",
+                    work_units: cells,
+                }
+            }
+            "matrix_lu" => {
+                let n = read_u32_be(input, 0)? as u64;
+                // Header, permutation, then L and U
+                let output_size = n
+                    .checked_mul(n)
+                    .and_then(|cells| cells.checked_mul(16))
+                    .and_then(|bytes| bytes.checked_add(4 + 4 * n))
+                    .and_then(|bytes| usize::try_from(bytes).ok());
+                CostEstimate {
+                    output_size,
+                    complexity: "O(n^3)",
+                    work_units: n.saturating_mul(n).saturating_mul(n),
+                }
+            }
+            "dot_product" => {
+                let count = read_u32_be(input, 0)? as u64;
+                CostEstimate {
+                    output_size: Some(8),
+                    complexity: "O(n)",
+                    work_units: count,
+                }
+            }
+            "normalize_vector" => {
+                let count = read_u32_be(input, 0)? as u64;
+                CostEstimate {
+                    output_size: Some(input.len()),
+                    complexity: "O(n)",
+                    work_units: count,
+                }
+            }
+            "moving_average" => {
+                let window = read_u32_be(input, 0)? as u64;
+                let count = read_u32_be(input, 4)? as u64;
+                let averages = count.saturating_sub(window.saturating_sub(1));
+                CostEstimate {
+                    output_size: usize::try_from(averages.saturating_mul(8).saturating_add(4)).ok(),
+                    complexity: "O(n*window)",
+                    work_units: averages.saturating_mul(window),
+                }
+            }
+            "kmeans_assign" => {
+                let dims = read_u32_be(input, 0)? as u64;
+                let centroids = read_u32_be(input, 4)? as u64;
+                let points = read_u32_be(input, 8)? as u64;
+                CostEstimate {
+                    output_size: usize::try_from(points.saturating_mul(4).saturating_add(4)).ok(),
+                    complexity: "O(points*centroids*dims)",
+                    work_units: points.saturating_mul(centroids).saturating_mul(dims),
+                }
+            }
+            "polynomial_eval" => {
+                let degree = read_u32_be(input, 0)? as u64;
+                let suffix = len.saturating_sub(12);
+                CostEstimate {
+                    output_size: Some(8),
+                    complexity: "O(degree*n)",
+                    work_units: (degree + 1).saturating_mul(suffix.max(1)),
+                }
+            }
+            "fibonacci" => {
+                let n = *input
+                    .first()
+                    .ok_or("No input provided for Fibonacci computation")?;
+                if n > 93 {
+                    return Err("Fibonacci number too large".into());
+                }
+                CostEstimate {
+                    output_size: Some(8),
+                    complexity: "O(n)",
+                    work_units: n as u64,
+                }
+            }
+            "fibonacci_sequence" => {
+                let n = *input
+                    .first()
+                    .ok_or("No input provided for Fibonacci sequence")?
+                    as usize;
+                if n > 93 {
+                    return Err("Fibonacci sequence overflows u64".into());
+                }
+                CostEstimate {
+                    output_size: Some(8 * (n + 1)),
+                    complexity: "O(n)",
+                    work_units: n as u64,
+                }
+            }
+            "factorize" => {
+                let n = read_u64_be(input, 0)?;
+                CostEstimate {
+                    output_size: None,
+                    complexity: "O(sqrt(n))",
+                    work_units: n.isqrt(),
+                }
+            }
+            "sort" => {
+                let count = read_u32_be(input, 0)? as u64;
+                let log_count = (u64::BITS - count.leading_zeros()) as u64;
+                CostEstimate {
+                    output_size: Some(input.len()),
+                    complexity: "O(n log n)",
+                    work_units: count.saturating_mul(log_count.max(1)),
+                }
+            }
+            "shuffle" => CostEstimate {
+                output_size: Some(input.len()),
+                complexity: "O(n)",
+                work_units: read_u32_be(input, 0)? as u64,
+            },
+            "base_convert" => {
+                let digits = input.len().saturating_sub(2) as u64;
+                CostEstimate {
+                    output_size: None,
+                    complexity: "O(n^2)",
+                    work_units: digits.saturating_mul(digits),
+                }
+            }
+            "sample" => {
+                let k = read_u32_be(input, 0)? as usize;
+                let count = read_u32_be(input, 4)? as usize;
+                CostEstimate {
+                    output_size: Some(k.min(count).saturating_mul(8).saturating_add(4)),
+                    complexity: "O(n)",
+                    work_units: count as u64,
+                }
+            }
+            "stratified_sample" => {
+                let k = read_u32_be(input, 0)? as usize;
+                let count = read_u32_be(input, 4)? as u64;
+                let log_count = (u64::BITS - count.leading_zeros()) as u64;
+                CostEstimate {
+                    output_size: None,
+                    complexity: "O(n log n)",
+                    work_units: count
+                        .saturating_mul(log_count.max(1))
+                        .saturating_add(k as u64),
+                }
+            }
+            "top_k" => {
+                let k = read_u32_be(input, 0)? as u64;
+                let count = read_u32_be(input, 4)? as u64;
+                let log_k = (u64::BITS - k.leading_zeros()) as u64;
+                CostEstimate {
+                    output_size: Some((k.min(count) as usize).saturating_mul(8).saturating_add(4)),
+                    complexity: "O(n log k)",
+                    work_units: count.saturating_mul(log_k.max(1)),
+                }
+            }
+            "rolling_hash" => CostEstimate {
+                output_size: None,
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "set_union" | "set_intersection" => CostEstimate {
+                output_size: None,
+                complexity: "O(n)",
+                work_units: len / 8,
+            },
+            // Every value is kept, so the output is the input minus one count prefix
+            "merge_sorted" => CostEstimate {
+                output_size: Some(len.saturating_sub(4) as usize),
+                complexity: "O(n)",
+                work_units: len / 8,
+            },
+            "histogram" => {
+                let buckets = read_u32_be(input, 0)? as usize;
+                CostEstimate {
+                    output_size: Some(buckets.saturating_mul(8).saturating_add(4)),
+                    complexity: "O(n + buckets)",
+                    work_units: read_u32_be(input, 4)? as u64 + buckets as u64,
+                }
+            }
+            "quantile" => {
+                let count = read_u32_be(input, 8)? as u64;
+                let log_count = (u64::BITS - count.leading_zeros()) as u64;
+                CostEstimate {
+                    output_size: Some(8),
+                    complexity: "O(n log n)",
+                    work_units: count.saturating_mul(log_count.max(1)),
+                }
+            }
+            "normalize_utf8" => CostEstimate {
+                output_size: None,
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "crc32" => CostEstimate {
+                output_size: Some(4),
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "crc64" => CostEstimate {
+                output_size: Some(8),
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "base64_encode" => CostEstimate {
+                output_size: Some(BASE64.encode_len(input.len())),
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "base32_encode" => CostEstimate {
+                output_size: Some(BASE32.encode_len(input.len())),
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "base64_decode" | "base32_decode" => CostEstimate {
+                output_size: None,
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "rle_encode" => CostEstimate {
+                output_size: None,
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "rle_decode" => CostEstimate {
+                output_size: None,
+                complexity: "O(n)",
+                work_units: len / 2 * 255,
+            },
+            "byte_diff" => {
+                let old_len = read_u32_be(input, 0)? as u64;
+                let new_len = len.saturating_sub(old_len + 8);
+                CostEstimate {
+                    output_size: None,
+                    complexity: "O(old*new)",
+                    work_units: (old_len + 1).saturating_mul(new_len + 1),
+                }
+            }
+            "byte_patch" => CostEstimate {
+                output_size: None,
+                complexity: "O(n)",
+                work_units: len,
+            },
+            "attest_reference" => CostEstimate {
+                output_size: Some(input.len()),
+                complexity: "O(n)",
+                work_units: len,
+            },
+            _ => return Err(format!("Unknown operation: {}", operation).into()),
         };
-        
-        // Cache and log
-        self.computation_cache.insert(computation_key.clone(), result.clone());
-        self.audit_logger.log_computation(&computation_key, operation, execution_time);
-        
-        Ok(result)
+
+        Ok(estimate)
     }
-    
+
     /// Deterministic hash function with seed integration
+    ///
+    /// The 8-byte seed prefix never fills a 64-byte SHA-256 block, so there is
+    /// no compressed midstate worth caching; cloning a pre-seeded hasher copies
+    /// more state than building a fresh one (see benchmark section 4).
     fn deterministic_hash(&self, input: &[u8]) -> Vec<u8> {
-        let mut hasher = Sha256::new();
-        hasher.update(self.seed.to_be_bytes());
+        let mut hasher = self.hasher.clone_state();
+        hasher.update(&self.seed.to_be_bytes());
         hasher.update(input);
-        hasher.finalize().to_vec()
+        let mut digest = hasher.finalize();
+        if let Some(len) = self.hash_truncation {
+            digest.truncate(len);
+        }
+        digest
     }
-    
+
     /// Deterministic encryption using ChaCha20
-    fn deterministic_encrypt(&mut self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    fn deterministic_encrypt(
+        &mut self,
+        input: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         self.crypto.encrypt_deterministic(input)
     }
-    
+
     /// Deterministic digital signature
     fn deterministic_sign(&mut self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         self.crypto.sign_deterministic(input)
     }
-    
+
     /// Matrix computation with deterministic operations
     fn matrix_computation(&mut self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         // Parse input as matrix dimensions and values
         if input.len() < 8 {
             return Err("Insufficient input for matrix computation".into());
         }
-        
-        let rows = u32::from_be_bytes([input[0], input[1], input[2], input[3]]) as usize;
-        let cols = u32::from_be_bytes([input[4], input[5], input[6], input[7]]) as usize;
-        
-        if input.len() < 8 + (rows * cols * 8) {
+
+        let rows = read_u32_be(input, 0)? as usize;
+        let cols = read_u32_be(input, 4)? as usize;
+
+        // Adversarial headers must not wrap the size check into a small number
+        let required = rows
+            .checked_mul(cols)
+            .and_then(|cells| cells.checked_mul(8))
+            .and_then(|bytes| bytes.checked_add(8))
+            .ok_or("Matrix dimensions overflow")?;
+        if input.len() < required {
             return Err("Insufficient data for matrix".into());
         }
-        
+
         // Create deterministic matrix
         let mut matrix: Vec<Vec<f64>> = vec![vec![0.0; cols]; rows];
         let mut offset = 8;
-        
+
         for i in 0..rows {
             for j in 0..cols {
-                let bytes = &input[offset..offset + 8];
-                matrix[i][j] = f64::from_be_bytes([
-                    bytes[0], bytes[1], bytes[2], bytes[3],
-                    bytes[4], bytes[5], bytes[6], bytes[7],
-                ]);
+                matrix[i][j] = read_f64_be(input, offset)?;
                 offset += 8;
             }
         }
-        
+
         // Perform deterministic matrix operations (example: transpose)
         let transposed = self.transpose_matrix(&matrix);
-        
+
         // Serialize result
         let mut result = Vec::new();
         result.extend_from_slice(&(cols as u32).to_be_bytes());
         result.extend_from_slice(&(rows as u32).to_be_bytes());
-        
+
         for row in transposed {
             for val in row {
                 result.extend_from_slice(&val.to_be_bytes());
             }
         }
-        
+
         Ok(result)
     }
-    
+
     /// Transpose matrix deterministically
     fn transpose_matrix(&self, matrix: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        // A header may claim zero rows, so there need not be a first row
         let rows = matrix.len();
-        let cols = matrix[0].len();
+        let cols = matrix.first().map_or(0, Vec::len);
         let mut transposed = vec![vec![0.0; rows]; cols];
-        
+
         for i in 0..rows {
             for j in 0..cols {
                 transposed[j][i] = matrix[i][j];
             }
         }
-        
+
         transposed
     }
-    
+
+    /// LU decomposition with partial pivoting, returning P, L and U
+    ///
+    /// Input layout matches `matrix_multiply` (rows, cols, row-major f64 BE
+    /// cells) and the matrix must be square and finite. Elimination runs
+    /// column by column; in each column the pivot is the row with the largest
+    /// absolute value, the lowest such row on ties, and every update is a
+    /// plain IEEE multiply then subtract, so factors are bit-for-bit
+    /// reproducible. A zero pivot means the matrix is singular and is an
+    /// error.
+    ///
+    /// The output is `n` (u32 BE), the permutation as `n` u32 BE row indices
+    /// (row `i` of `P * A` is row `perm[i]` of `A`), then unit lower
+    /// triangular `L` and upper triangular `U`, each `n * n` f64 BE cells in
+    /// row-major order, such that `P * A = L * U`.
+    fn lu_decomposition(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 8 {
+            return Err("Insufficient input for LU decomposition".into());
+        }
+
+        let rows = read_u32_be(input, 0)? as usize;
+        let cols = read_u32_be(input, 4)? as usize;
+        if rows != cols || rows == 0 {
+            return Err(format!(
+                "LU decomposition needs a non-empty square matrix, got {}x{}",
+                rows, cols
+            )
+            .into());
+        }
+        let n = rows;
+        let required = n
+            .checked_mul(n)
+            .and_then(|cells| cells.checked_mul(8))
+            .and_then(|bytes| bytes.checked_add(8))
+            .ok_or("Matrix dimensions overflow")?;
+        if input.len() != required {
+            return Err("LU input length does not match matrix dimensions".into());
+        }
+
+        let mut a = input[8..]
+            .chunks_exact(8)
+            .map(|chunk| read_f64_be(chunk, 0))
+            .collect::<Result<Vec<_>, ComputeError>>()?;
+        if a.iter().any(|value| !value.is_finite()) {
+            return Err("LU decomposition needs finite values".into());
+        }
+        let mut perm: Vec<u32> = (0..n as u32).collect();
+
+        // In place: multipliers of L below the diagonal, U on and above it
+        for k in 0..n {
+            let mut pivot = k;
+            for i in k + 1..n {
+                if a[i * n + k].abs() > a[pivot * n + k].abs() {
+                    pivot = i;
+                }
+            }
+            if a[pivot * n + k] == 0.0 {
+                return Err(
+                    format!("Matrix is singular: no non-zero pivot in column {}", k).into(),
+                );
+            }
+            if pivot != k {
+                for j in 0..n {
+                    a.swap(k * n + j, pivot * n + j);
+                }
+                perm.swap(k, pivot);
+            }
+
+            for i in k + 1..n {
+                let factor = a[i * n + k] / a[k * n + k];
+                a[i * n + k] = factor;
+                for j in k + 1..n {
+                    a[i * n + j] -= factor * a[k * n + j];
+                }
+            }
+        }
+
+        let mut result = Vec::with_capacity(4 + 4 * n + 16 * n * n);
+        result.extend_from_slice(&(n as u32).to_be_bytes());
+        for row in &perm {
+            result.extend_from_slice(&row.to_be_bytes());
+        }
+        for i in 0..n {
+            for j in 0..n {
+                let l = match i.cmp(&j) {
+                    std::cmp::Ordering::Greater => a[i * n + j],
+                    std::cmp::Ordering::Equal => 1.0,
+                    std::cmp::Ordering::Less => 0.0,
+                };
+                result.extend_from_slice(&l.to_be_bytes());
+            }
+        }
+        for i in 0..n {
+            for j in 0..n {
+                let u = if i <= j { a[i * n + j] } else { 0.0 };
+                result.extend_from_slice(&u.to_be_bytes());
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Dot product of two f64 vectors, accumulated strictly left to right
+    ///
+    /// Input layout: each vector as a length (u32 BE) followed by that many
+    /// f64 BE values; the lengths must match and every value must be finite.
+    /// The sum starts at 0.0 and adds `a[i] * b[i]` for ascending `i`, each
+    /// product rounded before it is added (no fused multiply-add, no pairwise
+    /// or compensated summation). Rounding therefore depends on element order
+    /// but is identical on every IEEE 754 platform. The output is one f64 BE.
+    fn dot_product(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut rest = input;
+        let a = take_f64_vector(&mut rest)?;
+        let b = take_f64_vector(&mut rest)?;
+        if !rest.is_empty() {
+            return Err("Trailing bytes after dot product operands".into());
+        }
+        if a.len() != b.len() {
+            return Err(format!(
+                "Dot product vectors differ in length: {} and {}",
+                a.len(),
+                b.len()
+            )
+            .into());
+        }
+        if a.iter().chain(&b).any(|value| !value.is_finite()) {
+            return Err("Dot product values must be finite".into());
+        }
+
+        let mut sum = 0.0f64;
+        for (x, y) in a.iter().zip(&b) {
+            sum += x * y;
+        }
+
+        Ok(sum.to_be_bytes().to_vec())
+    }
+
+    /// Scale an f64 vector to unit L2 norm
+    ///
+    /// Input and output share the layout of a length (u32 BE) followed by
+    /// that many f64 BE values, all finite. Values are first divided by the
+    /// largest magnitude so squaring cannot overflow or underflow; the squares
+    /// are then summed left to right as in `dot_product`, and each scaled
+    /// value is divided by the square root of that sum. A vector with no
+    /// non-zero value has no direction and is an error.
+    fn normalize_vector(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut rest = input;
+        let values = take_f64_vector(&mut rest)?;
+        if !rest.is_empty() {
+            return Err("Trailing bytes after vector".into());
+        }
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err("Vector values must be finite".into());
+        }
+        let scale = values
+            .iter()
+            .fold(0.0f64, |max, value| max.max(value.abs()));
+        if scale == 0.0 {
+            return Err("Cannot normalize a zero vector".into());
+        }
+
+        let scaled: Vec<f64> = values.iter().map(|value| value / scale).collect();
+        let mut sum = 0.0f64;
+        for value in &scaled {
+            sum += value * value;
+        }
+        let norm = sum.sqrt();
+
+        let mut output = Vec::with_capacity(input.len());
+        output.extend_from_slice(&(scaled.len() as u32).to_be_bytes());
+        for value in scaled {
+            output.extend_from_slice(&(value / norm).to_be_bytes());
+        }
+        Ok(output)
+    }
+
+    /// Trailing simple moving average of an f64 series
+    ///
+    /// Input layout: window size (u32 BE, at least 1), then the series as a
+    /// length (u32 BE) followed by that many finite f64 BE values. Only full
+    /// windows are averaged, so a series of `n` values yields `n - window + 1`
+    /// averages and a window longer than the series is an error. Each window
+    /// is summed left to right from 0.0 and divided by the window size; sums
+    /// are not carried between windows, so no rounding error accumulates
+    /// along the series. The output is a length (u32 BE) followed by the
+    /// averages as f64 BE, in series order.
+    fn moving_average(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let window = read_u32_be(input, 0)? as usize;
+        let mut rest = &input[4..];
+        let series = take_f64_vector(&mut rest)?;
+        if !rest.is_empty() {
+            return Err("Trailing bytes after series".into());
+        }
+        if window == 0 || window > series.len() {
+            return Err(format!(
+                "Moving average window {} outside 1..={}",
+                window,
+                series.len()
+            )
+            .into());
+        }
+        if series.iter().any(|value| !value.is_finite()) {
+            return Err("Series values must be finite".into());
+        }
+
+        let averages = series.windows(window).map(|values| {
+            let mut sum = 0.0f64;
+            for value in values {
+                sum += value;
+            }
+            sum / window as f64
+        });
+
+        let mut output = Vec::with_capacity(4 + (series.len() - window + 1) * 8);
+        output.extend_from_slice(&((series.len() - window + 1) as u32).to_be_bytes());
+        for average in averages {
+            output.extend_from_slice(&average.to_be_bytes());
+        }
+        Ok(output)
+    }
+
+    /// One k-means assignment step: the nearest centroid for every point
+    ///
+    /// Input layout: dimensions (u32 BE, at least 1), centroid count (u32 BE,
+    /// at least 1), point count (u32 BE), then the centroids and then the
+    /// points, each as `dimensions` f64 BE values; every value must be finite.
+    /// Distance is the squared Euclidean distance, with squared differences
+    /// summed in dimension order and no square root taken. A point goes to
+    /// the centroid with the smallest distance, and on a tie to the lowest
+    /// index. The output is the point count (u32 BE) followed by one centroid
+    /// index (u32 BE) per point, in input order.
+    fn kmeans_assign(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 12 {
+            return Err("Insufficient input for kmeans_assign".into());
+        }
+        let dims = read_u32_be(input, 0)? as usize;
+        let centroid_count = read_u32_be(input, 4)? as usize;
+        let point_count = read_u32_be(input, 8)? as usize;
+        if dims == 0 || centroid_count == 0 {
+            return Err("kmeans_assign needs at least one dimension and one centroid".into());
+        }
+        let body_len = centroid_count
+            .checked_add(point_count)
+            .and_then(|vectors| vectors.checked_mul(dims))
+            .and_then(|values| values.checked_mul(8))
+            .ok_or("kmeans_assign input size overflows")?;
+        if input.len() - 12 != body_len {
+            return Err("kmeans_assign input length does not match its header".into());
+        }
+
+        let values = input[12..]
+            .chunks_exact(8)
+            .map(|chunk| read_f64_be(chunk, 0))
+            .collect::<Result<Vec<_>, ComputeError>>()?;
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err("kmeans_assign values must be finite".into());
+        }
+        let (centroids, points) = values.split_at(centroid_count * dims);
+
+        let mut result = Vec::with_capacity(4 + point_count * 4);
+        result.extend_from_slice(&(point_count as u32).to_be_bytes());
+        for point in points.chunks_exact(dims) {
+            let mut nearest = (0, f64::INFINITY);
+            for (index, centroid) in centroids.chunks_exact(dims).enumerate() {
+                let mut distance = 0.0f64;
+                for (p, c) in point.iter().zip(centroid) {
+                    distance += (p - c) * (p - c);
+                }
+                // Strictly less, so ties keep the lower index
+                if distance < nearest.1 {
+                    nearest = (index, distance);
+                }
+            }
+            result.extend_from_slice(&(nearest.0 as u32).to_be_bytes());
+        }
+
+        Ok(result)
+    }
+
     /// Polynomial evaluation with deterministic coefficients
-    fn polynomial_evaluation(&mut self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    ///
+    /// Coefficient `i` is derived from SHA-256(`seed + i` || `input[12..]`).
+    /// The per-term seed comes first, so the shared suffix cannot be absorbed
+    /// once into a reusable midstate without changing every coefficient; the
+    /// O(degree × input) cost is the price of keeping existing outputs stable
+    /// (see benchmark section 6).
+    fn polynomial_evaluation(
+        &mut self,
+        input: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         if input.len() < 16 {
             return Err("Insufficient input for polynomial evaluation".into());
         }
-        
-        let degree = u32::from_be_bytes([input[0], input[1], input[2], input[3]]) as usize;
-        let x_value = f64::from_be_bytes([
-            input[4], input[5], input[6], input[7],
-            input[8], input[9], input[10], input[11],
-        ]);
-        
+
+        let degree = read_u32_be(input, 0)? as usize;
+        let x_value = read_f64_be(input, 4)?;
+
         // Generate deterministic coefficients
         let mut coefficients = Vec::with_capacity(degree + 1);
         for i in 0..=degree {
@@ -215,208 +1437,3945 @@ impl DeterministicEngine {
             hasher.update(seed_bytes);
             hasher.update(&input[12..]);
             let hash = hasher.finalize();
-            
+
             // Convert hash to coefficient
-            let coeff_bytes = [hash[0], hash[1], hash[2], hash[3], hash[4], hash[5], hash[6], hash[7]];
+            let coeff_bytes = [
+                hash[0], hash[1], hash[2], hash[3], hash[4], hash[5], hash[6], hash[7],
+            ];
             let coefficient = f64::from_be_bytes(coeff_bytes) / 1e18; // Normalize
             coefficients.push(coefficient);
         }
-        
+
         // Evaluate polynomial: a0 + a1*x + a2*x^2 + ... + an*x^n
         let mut result = 0.0;
         let mut x_power = 1.0;
-        
+
         for coeff in coefficients {
             result += coeff * x_power;
             x_power *= x_value;
         }
-        
+
+        Ok(result.to_be_bytes().to_vec())
+    }
+
+    /// Stable sort of fixed-width records by their u64 key
+    ///
+    /// Input layout: record count (u32 BE), payload width in bytes (u32 BE), then
+    /// `count` records of a u64 BE key followed by `width` payload bytes. A width
+    /// of zero sorts bare u64 values. Records with equal keys keep their original
+    /// relative order, so the output never depends on the sort implementation.
+    fn sort_records(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 8 {
+            return Err("Insufficient input for sort".into());
+        }
+
+        let count = read_u32_be(input, 0)? as usize;
+        let payload_width = read_u32_be(input, 4)? as usize;
+        let record_width = payload_width
+            .checked_add(8)
+            .ok_or("Sort record width overflows")?;
+        let body_len = count
+            .checked_mul(record_width)
+            .ok_or("Sort input size overflows")?;
+
+        if input.len() - 8 != body_len {
+            return Err("Sort input length does not match record header".into());
+        }
+
+        let mut records = input[8..]
+            .chunks_exact(record_width)
+            .map(|record| Ok((read_u64_be(record, 0)?, record)))
+            .collect::<Result<Vec<_>, ComputeError>>()?;
+        // `sort_by_key` is stable: equal keys stay in original index order
+        records.sort_by_key(|(key, _)| *key);
+
+        let mut result = Vec::with_capacity(input.len());
+        result.extend_from_slice(&input[..8]);
+        for (_, record) in records {
+            result.extend_from_slice(record);
+        }
+
+        Ok(result)
+    }
+
+    /// Re-encode an integer digit string from one radix into another
+    ///
+    /// Input layout: source radix (u8), target radix (u8), then the integer's
+    /// digits as ASCII, most significant first. Radices range over 2..=36 and
+    /// letters are case-insensitive. The output is the lowercase ASCII digit
+    /// string in the target radix, without leading zeros ("0" for zero).
+    fn base_convert(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 3 {
+            return Err("Insufficient input for base conversion".into());
+        }
+
+        let (source_radix, target_radix) = (input[0] as u32, input[1] as u32);
+        for radix in [source_radix, target_radix] {
+            if !(2..=36).contains(&radix) {
+                return Err(format!("Radix {} outside supported range 2..=36", radix).into());
+            }
+        }
+
+        let digits = input[2..]
+            .iter()
+            .map(|&byte| {
+                (byte as char)
+                    .to_digit(source_radix)
+                    .map(|digit| digit as u8)
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| format!("Invalid digit for radix {}", source_radix))?;
+        let value = BigUint::from_radix_be(&digits, source_radix)
+            .ok_or("Invalid digits for base conversion")?;
+
+        Ok(value.to_str_radix(target_radix).into_bytes())
+    }
+
+    /// ChaCha20 RNG for randomized operations, keyed by seed and input
+    ///
+    /// Seeded with the engine seed, on a stream selected by the input's hash,
+    /// rather than drawn from `self.rng`: advancing the shared RNG would make
+    /// results depend on which computations ran before them.
+    fn input_rng(&self, input: &[u8]) -> ChaCha20Rng {
+        let stream = Sha256::digest(input);
+        let mut rng = ChaCha20Rng::seed_from_u64(self.seed);
+        rng.set_stream(u64::from_be_bytes([
+            stream[0], stream[1], stream[2], stream[3], stream[4], stream[5], stream[6], stream[7],
+        ]));
+        rng
+    }
+
+    /// Fisher-Yates shuffle of a list of u64 values
+    ///
+    /// Input layout: value count (u32 BE) followed by that many u64 BE values;
+    /// the output uses the same layout. Swaps come from `input_rng`, and indices
+    /// are sampled as u64 so 32- and 64-bit targets produce the same order.
+    fn shuffle_values(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 4 {
+            return Err("Insufficient input for shuffle".into());
+        }
+
+        let count = read_u32_be(input, 0)? as usize;
+        let body_len = count.checked_mul(8).ok_or("Shuffle input size overflows")?;
+        if input.len() - 4 != body_len {
+            return Err("Shuffle input length does not match value count".into());
+        }
+
+        let mut values = input[4..]
+            .chunks_exact(8)
+            .map(|chunk| read_u64_be(chunk, 0))
+            .collect::<Result<Vec<_>, ComputeError>>()?;
+
+        let mut rng = self.input_rng(input);
+        for i in (1..values.len()).rev() {
+            let j = rng.gen_range(0..=i as u64) as usize;
+            values.swap(i, j);
+        }
+
+        let mut result = Vec::with_capacity(input.len());
+        result.extend_from_slice(&input[..4]);
+        for value in values {
+            result.extend_from_slice(&value.to_be_bytes());
+        }
+
+        Ok(result)
+    }
+
+    /// Reservoir sample of `k` values from a list of u64 values
+    ///
+    /// Input layout: sample size `k` (u32 BE), value count (u32 BE), then that
+    /// many u64 BE values. The output is the count of selected values (u32 BE,
+    /// `min(k, n)`) followed by the values in reservoir order. Replacement
+    /// indices come from `input_rng`, sampled as u64 for portability.
+    fn sample_values(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 8 {
+            return Err("Insufficient input for sample".into());
+        }
+
+        let k = read_u32_be(input, 0)? as usize;
+        let count = read_u32_be(input, 4)? as usize;
+        let body_len = count.checked_mul(8).ok_or("Sample input size overflows")?;
+        if input.len() - 8 != body_len {
+            return Err("Sample input length does not match value count".into());
+        }
+
+        let values = input[8..]
+            .chunks_exact(8)
+            .map(|chunk| read_u64_be(chunk, 0))
+            .collect::<Result<Vec<_>, ComputeError>>()?;
+        let reservoir = reservoir_sample(values, k, &mut self.input_rng(input));
+
+        let mut result = Vec::with_capacity(4 + reservoir.len() * 8);
+        result.extend_from_slice(&(reservoir.len() as u32).to_be_bytes());
+        for value in reservoir {
+            result.extend_from_slice(&value.to_be_bytes());
+        }
+
+        Ok(result)
+    }
+
+    /// Reservoir sample of `k` values from each quartile of a list of u64 values
+    ///
+    /// Input layout matches `sample`, with `k` counted per stratum. Values are
+    /// ranked ascending, equal values by input position, and stratum `s` of
+    /// `n` values holds ranks `s * n / 4` up to, not including,
+    /// `(s + 1) * n / 4`, so stratum sizes differ by at most one.
+    /// Each stratum keeps its members in input order and is sampled as
+    /// `sample` samples, lowest stratum first, all from one `input_rng`
+    /// stream. The output is four U64 lists (count as u32 BE, then the
+    /// values), lowest stratum first, each holding `min(k, stratum size)`.
+    fn stratified_sample(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 8 {
+            return Err("Insufficient input for stratified_sample".into());
+        }
+
+        let k = read_u32_be(input, 0)? as usize;
+        let count = read_u32_be(input, 4)? as usize;
+        let body_len = count
+            .checked_mul(8)
+            .ok_or("Stratified sample input size overflows")?;
+        if input.len() - 8 != body_len {
+            return Err("Stratified sample input length does not match value count".into());
+        }
+        let values = input[8..]
+            .chunks_exact(8)
+            .map(|chunk| read_u64_be(chunk, 0))
+            .collect::<Result<Vec<_>, ComputeError>>()?;
+
+        // Stable, so equal values stay in input order
+        let mut ranked: Vec<usize> = (0..count).collect();
+        ranked.sort_by_key(|&index| values[index]);
+
+        let mut rng = self.input_rng(input);
+        let mut result = Vec::new();
+        for stratum in 0..4 {
+            let mut members = ranked[stratum * count / 4..(stratum + 1) * count / 4].to_vec();
+            members.sort_unstable();
+            let sample =
+                reservoir_sample(members.into_iter().map(|index| values[index]), k, &mut rng);
+
+            result.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+            for value in sample {
+                result.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// The `k` largest of a list of u64 values, largest first
+    ///
+    /// Input layout matches `sample`: `k` (u32 BE), value count (u32 BE), then
+    /// that many u64 BE values. Equal values are ranked by their position in
+    /// the input, earliest first, so ties at the cut-off always keep the same
+    /// elements. A min-heap of at most `k` entries avoids sorting the whole
+    /// list. The output is the count of selected values (u32 BE, `min(k, n)`)
+    /// followed by the values in descending order.
+    fn top_k(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 8 {
+            return Err("Insufficient input for top_k".into());
+        }
+
+        let k = read_u32_be(input, 0)? as usize;
+        let count = read_u32_be(input, 4)? as usize;
+        let body_len = count.checked_mul(8).ok_or("top_k input size overflows")?;
+        if input.len() - 8 != body_len {
+            return Err("top_k input length does not match value count".into());
+        }
+
+        // Ranked by (value, earlier index); the heap root is the weakest kept entry
+        let mut heap = BinaryHeap::with_capacity(k.min(count));
+        for (index, chunk) in input[8..].chunks_exact(8).enumerate() {
+            let entry = Reverse((read_u64_be(chunk, 0)?, Reverse(index)));
+            if heap.len() < k {
+                heap.push(entry);
+            } else if let Some(mut weakest) = heap.peek_mut() {
+                if entry < *weakest {
+                    *weakest = entry;
+                }
+            }
+        }
+
+        let selected = heap.into_sorted_vec();
+        let mut result = Vec::with_capacity(4 + selected.len() * 8);
+        result.extend_from_slice(&(selected.len() as u32).to_be_bytes());
+        for Reverse((value, _)) in selected {
+            result.extend_from_slice(&value.to_be_bytes());
+        }
+
+        Ok(result)
+    }
+
+    /// Content-defined chunk boundaries from a Rabin-Karp rolling hash
+    ///
+    /// Input layout: mask width in bits (u8, 1..=32) followed by the data. A
+    /// polynomial hash over the last `ROLLING_HASH_WINDOW` bytes is updated at
+    /// every position, and a boundary is cut after any byte where the top
+    /// `mask_bits` bits of the hash are zero, giving chunks of roughly
+    /// `2^mask_bits` bytes. The output is the boundary count (u32 BE) followed
+    /// by each boundary as an exclusive end offset (u64 BE).
+    ///
+    /// The hash is not seeded, so boundaries depend on content alone and a
+    /// byte edit only moves boundaries within one window of the change.
+    fn rolling_hash_boundaries(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let (&mask_bits, data) = input
+            .split_first()
+            .ok_or("No input provided for rolling hash")?;
+        if !(1..=32).contains(&mask_bits) {
+            return Err(format!("Rolling hash mask of {} bits outside 1..=32", mask_bits).into());
+        }
+
+        // Weight of the byte leaving the window: BASE^WINDOW
+        let outgoing_weight =
+            (0..ROLLING_HASH_WINDOW).fold(1u64, |power, _| power.wrapping_mul(ROLLING_HASH_BASE));
+        let mut hash = 0u64;
+        let mut boundaries = Vec::new();
+        for (i, &byte) in data.iter().enumerate() {
+            hash = hash
+                .wrapping_mul(ROLLING_HASH_BASE)
+                .wrapping_add(byte as u64 + 1);
+            if i >= ROLLING_HASH_WINDOW {
+                hash = hash.wrapping_sub(
+                    (data[i - ROLLING_HASH_WINDOW] as u64 + 1).wrapping_mul(outgoing_weight),
+                );
+            }
+            if i + 1 >= ROLLING_HASH_WINDOW && hash >> (64 - mask_bits) == 0 {
+                boundaries.push(i as u64 + 1);
+            }
+        }
+
+        let mut result = Vec::with_capacity(4 + boundaries.len() * 8);
+        result.extend_from_slice(&(boundaries.len() as u32).to_be_bytes());
+        for boundary in boundaries {
+            result.extend_from_slice(&boundary.to_be_bytes());
+        }
+
+        Ok(result)
+    }
+
+    /// Union, intersection or merge of two sorted u64 lists
+    ///
+    /// Input layout: two lists back to back, each a value count (u32 BE)
+    /// followed by that many u64 BE values in ascending order. The output is a
+    /// single list in the same layout, ascending. Union and intersection
+    /// collapse repeated values; a merge keeps every value from both lists,
+    /// taking equal values from the first list before the second.
+    fn set_operation(
+        &self,
+        input: &[u8],
+        operation: SetOperation,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let keep_duplicates = operation == SetOperation::Merge;
+        let mut rest = input;
+        let left = take_sorted_u64_list(&mut rest, keep_duplicates)?;
+        let right = take_sorted_u64_list(&mut rest, keep_duplicates)?;
+        if !rest.is_empty() {
+            return Err("Trailing bytes after set operands".into());
+        }
+
+        let mut values = Vec::with_capacity(left.len() + right.len());
+        let (mut i, mut j) = (0, 0);
+        while i < left.len() && j < right.len() {
+            match left[i].cmp(&right[j]) {
+                std::cmp::Ordering::Less => {
+                    if operation != SetOperation::Intersection {
+                        values.push(left[i]);
+                    }
+                    i += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    if operation != SetOperation::Intersection {
+                        values.push(right[j]);
+                    }
+                    j += 1;
+                }
+                // A merge leaves the right value for a later step, after every equal left value
+                std::cmp::Ordering::Equal if operation == SetOperation::Merge => {
+                    values.push(left[i]);
+                    i += 1;
+                }
+                std::cmp::Ordering::Equal => {
+                    values.push(left[i]);
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        if operation != SetOperation::Intersection {
+            values.extend_from_slice(&left[i..]);
+            values.extend_from_slice(&right[j..]);
+        }
+
+        let mut result = Vec::with_capacity(4 + values.len() * 8);
+        result.extend_from_slice(&(values.len() as u32).to_be_bytes());
+        for value in values {
+            result.extend_from_slice(&value.to_be_bytes());
+        }
+
+        Ok(result)
+    }
+
+    /// Frequency histogram of u64 values over equal-width buckets
+    ///
+    /// Input layout: bucket count (u32 BE, 1..=`MAX_HISTOGRAM_BUCKETS`), value
+    /// count (u32 BE), then that many u64 BE values. The inclusive range
+    /// `min..=max` is split with integer arithmetic: value `v` falls in bucket
+    /// `(v - min) * buckets / (max - min + 1)`, so boundaries never depend on
+    /// floating-point rounding. All-equal values land in bucket 0, and empty
+    /// input yields all-zero counts. The output is the bucket count (u32 BE)
+    /// followed by each bucket's count (u64 BE).
+    fn histogram(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 8 {
+            return Err("Insufficient input for histogram".into());
+        }
+
+        let buckets = read_u32_be(input, 0)? as usize;
+        if !(1..=MAX_HISTOGRAM_BUCKETS).contains(&buckets) {
+            return Err(format!(
+                "Histogram bucket count {} outside 1..={}",
+                buckets, MAX_HISTOGRAM_BUCKETS
+            )
+            .into());
+        }
+        let count = read_u32_be(input, 4)? as usize;
+        let body_len = count
+            .checked_mul(8)
+            .ok_or("Histogram input size overflows")?;
+        if input.len() - 8 != body_len {
+            return Err("Histogram input length does not match value count".into());
+        }
+
+        let values = input[8..]
+            .chunks_exact(8)
+            .map(|chunk| read_u64_be(chunk, 0))
+            .collect::<Result<Vec<_>, ComputeError>>()?;
+
+        let mut counts = vec![0u64; buckets];
+        if let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) {
+            // u128 keeps `max - min + 1` and the product exact for the full u64 range
+            let span = (max - min) as u128 + 1;
+            for value in values {
+                let bucket = (value - min) as u128 * buckets as u128 / span;
+                counts[bucket as usize] += 1;
+            }
+        }
+
+        let mut result = Vec::with_capacity(4 + buckets * 8);
+        result.extend_from_slice(&(buckets as u32).to_be_bytes());
+        for count in counts {
+            result.extend_from_slice(&count.to_be_bytes());
+        }
+
+        Ok(result)
+    }
+
+    /// Value at quantile `q` of a list of f64 values
+    ///
+    /// Input layout: `q` (f64 BE, in `0.0..=1.0`), value count (u32 BE, at
+    /// least 1), then that many f64 BE values. NaN and infinite inputs are
+    /// rejected. The values are sorted, and the result is linearly
+    /// interpolated between the two closest ranks. This is Hyndman and Fan's
+    /// method 7, the default in R and NumPy: with `h = (n - 1) * q`, the
+    /// result is `x[floor(h)] + (h - floor(h)) * (x[floor(h) + 1] - x[floor(h)])`.
+    /// The output is the result as an f64 BE.
+    fn quantile(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() < 12 {
+            return Err("Insufficient input for quantile".into());
+        }
+
+        let q = read_f64_be(input, 0)?;
+        if !(0.0..=1.0).contains(&q) {
+            return Err(format!("Quantile {} outside 0..=1", q).into());
+        }
+        let count = read_u32_be(input, 8)? as usize;
+        let body_len = count
+            .checked_mul(8)
+            .ok_or("Quantile input size overflows")?;
+        if count == 0 || input.len() - 12 != body_len {
+            return Err("Quantile input length does not match a non-zero value count".into());
+        }
+
+        let mut values = input[12..]
+            .chunks_exact(8)
+            .map(|chunk| read_f64_be(chunk, 0))
+            .collect::<Result<Vec<_>, ComputeError>>()?;
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err("Quantile values must be finite".into());
+        }
+        values.sort_by(f64::total_cmp);
+
+        let h = (count - 1) as f64 * q;
+        let lower = h.floor() as usize;
+        let upper = (lower + 1).min(count - 1);
+        let result = values[lower] + (h - lower as f64) * (values[upper] - values[lower]);
+
         Ok(result.to_be_bytes().to_vec())
     }
-    
+
+    /// CRC-32/ISO-HDLC over the seed (u64 BE) followed by the input, as 4 BE bytes
+    ///
+    /// CRCs are not cryptographically secure: collisions are trivial to
+    /// construct. Use them for indexing and corruption checks only, and the
+    /// `hash` operation wherever an adversary could choose the input.
+    fn seeded_crc32(&self, input: &[u8]) -> Vec<u8> {
+        let mut digest = CRC32.digest();
+        digest.update(&self.seed.to_be_bytes());
+        digest.update(input);
+        digest.finalize().to_be_bytes().to_vec()
+    }
+
+    /// CRC-64/XZ over the seed (u64 BE) followed by the input, as 8 BE bytes
+    ///
+    /// Like `crc32`, this is for indexing only and offers no security.
+    fn seeded_crc64(&self, input: &[u8]) -> Vec<u8> {
+        let mut digest = CRC64.digest();
+        digest.update(&self.seed.to_be_bytes());
+        digest.update(input);
+        digest.finalize().to_be_bytes().to_vec()
+    }
+
+    /// Strict RFC 4648 decoding of padded Base64 or Base32 text
+    ///
+    /// `base64_encode` and `base32_encode` use the standard alphabets with
+    /// padding. Decoding accepts only their canonical output: whitespace,
+    /// missing or extra padding, symbols outside the alphabet and non-zero
+    /// trailing bits are all errors, so every decoded value has exactly one
+    /// encoding and nothing is dropped silently.
+    fn decode_text(
+        &self,
+        input: &[u8],
+        encoding: &data_encoding::Encoding,
+        name: &str,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        encoding
+            .decode(input)
+            .map_err(|e| format!("Invalid {} input: {}", name, e).into())
+    }
+
+    /// Run-length encoding as (count, byte) pairs
+    ///
+    /// Each maximal run of a byte becomes pairs of a count (u8, 1..=255) and
+    /// the byte, with runs longer than 255 split into full pairs first. Input
+    /// without repeats doubles in size; the format has no escape mechanism.
+    fn rle_encode(&self, input: &[u8]) -> Vec<u8> {
+        let mut result = Vec::new();
+        for run in input.chunk_by(|a, b| a == b) {
+            for piece in run.chunks(u8::MAX as usize) {
+                result.push(piece.len() as u8);
+                result.push(piece[0]);
+            }
+        }
+        result
+    }
+
+    /// Decodes `rle_encode` output, accepting only its canonical form
+    ///
+    /// The stream must be whole pairs with non-zero counts, and a pair with a
+    /// count below 255 cannot be followed by another pair of the same byte.
+    /// Every decoded value therefore has exactly one valid encoding.
+    fn rle_decode(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let pairs = input.chunks_exact(2);
+        if !pairs.remainder().is_empty() {
+            return Err("RLE input must be whole (count, byte) pairs".into());
+        }
+
+        let mut result = Vec::new();
+        let mut previous: Option<(u8, u8)> = None;
+        for (index, pair) in pairs.enumerate() {
+            let (count, byte) = (pair[0], pair[1]);
+            if count == 0 {
+                return Err(format!("RLE pair {} has a zero count", index).into());
+            }
+            if matches!(previous, Some((previous_count, previous_byte)) if previous_byte == byte && previous_count < u8::MAX)
+            {
+                return Err(format!(
+                    "RLE pair {} continues a run that should have been merged",
+                    index
+                )
+                .into());
+            }
+            result.resize(result.len() + count as usize, byte);
+            previous = Some((count, byte));
+        }
+
+        Ok(result)
+    }
+
+    /// Edit script turning one byte string into another
+    ///
+    /// Input layout: the old and then the new string, each as a length
+    /// (u32 BE) followed by its bytes. The longest common prefix and suffix
+    /// are copied as-is; the bytes between them are aligned by a longest
+    /// common subsequence, walked from the front, taking a match whenever the
+    /// bytes agree and otherwise preferring a deletion over an insertion when
+    /// both keep the subsequence longest. That middle table is limited to
+    /// `MAX_DIFF_CELLS` entries.
+    ///
+    /// The output is a list of ops, each a tag byte and a u32 BE length:
+    /// `DIFF_COPY` and `DIFF_DELETE` consume that many old bytes, and
+    /// `DIFF_INSERT` is followed by that many new bytes. Between two copies
+    /// all deletions come first, merged into one op, then all insertions, so
+    /// equal inputs always give the same, compact script.
+    fn byte_diff(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut rest = input;
+        let old = take_byte_string(&mut rest)?;
+        let new = take_byte_string(&mut rest)?;
+        if !rest.is_empty() {
+            return Err("Trailing bytes after diff operands".into());
+        }
+
+        let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let old_middle = &old[prefix..old.len() - suffix];
+        let new_middle = &new[prefix..new.len() - suffix];
+
+        let width = new_middle.len() + 1;
+        let cells = (old_middle.len() + 1)
+            .checked_mul(width)
+            .filter(|&cells| cells <= MAX_DIFF_CELLS)
+            .ok_or_else(|| {
+                format!(
+                    "Diff of {} by {} differing bytes exceeds the limit of {} cells",
+                    old_middle.len(),
+                    new_middle.len(),
+                    MAX_DIFF_CELLS
+                )
+            })?;
+
+        // lcs[i * width + j] is the LCS length of old_middle[i..] and new_middle[j..]
+        let mut lcs = vec![0u32; cells];
+        for i in (0..old_middle.len()).rev() {
+            for j in (0..new_middle.len()).rev() {
+                lcs[i * width + j] = if old_middle[i] == new_middle[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let mut script = DiffScript::default();
+        script.copy(prefix);
+        let (mut i, mut j) = (0, 0);
+        while i < old_middle.len() || j < new_middle.len() {
+            if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+                script.copy(1);
+                i += 1;
+                j += 1;
+            } else if j == new_middle.len()
+                || (i < old_middle.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                script.delete();
+                i += 1;
+            } else {
+                script.insert(new_middle[j]);
+                j += 1;
+            }
+        }
+        script.copy(suffix);
+
+        Ok(script.finish())
+    }
+
+    /// Applies a `byte_diff` script to the old string it was made from
+    ///
+    /// Input layout: the old string as a length (u32 BE) followed by its
+    /// bytes, then the ops to the end of the input. Ops must stay within the
+    /// old string and consume all of it, so a script only applies to a string
+    /// of the length it was made for. The output is the new string.
+    fn byte_patch(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut ops = input;
+        let old = take_byte_string(&mut ops)?;
+
+        let mut result = Vec::new();
+        let mut position = 0usize;
+        while let Some((&tag, rest)) = ops.split_first() {
+            let len = read_u32_be(rest, 0)? as usize;
+            ops = &rest[4..];
+            match tag {
+                DIFF_COPY | DIFF_DELETE => {
+                    let source = position
+                        .checked_add(len)
+                        .and_then(|end| old.get(position..end))
+                        .ok_or("Diff op runs past the end of the old string")?;
+                    if tag == DIFF_COPY {
+                        result.extend_from_slice(source);
+                    }
+                    position += len;
+                }
+                DIFF_INSERT => {
+                    let inserted = ops
+                        .get(..len)
+                        .ok_or("Diff insert shorter than its length")?;
+                    result.extend_from_slice(inserted);
+                    ops = &ops[len..];
+                }
+                _ => return Err(format!("Unknown diff op tag {}", tag).into()),
+            }
+        }
+        if position != old.len() {
+            return Err(format!("Diff consumed {} of {} old bytes", position, old.len()).into());
+        }
+
+        Ok(result)
+    }
+
+    /// Attest that a URI refers to content with a given SHA-256 digest
+    ///
+    /// The input is a `ReferenceAttestation` encoding, returned unchanged once
+    /// it parses, so the result signs the binding without the content ever
+    /// being fetched. The attested time is part of the input rather than read
+    /// from the engine clock, keeping the operation a pure function of its
+    /// input like every other; the result's own `timestamp` still records
+    /// when the engine produced it. `VerifierEngine::verify_reference` checks
+    /// the result and the content it refers to.
+    fn attest_reference(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        ReferenceAttestation::parse(input)?;
+        Ok(input.to_vec())
+    }
+
+    /// Unicode NFC normalization of UTF-8 text
+    ///
+    /// The input must be valid UTF-8 and the output is its NFC form, so names
+    /// that differ only in composition (precomposed `é` versus `e` plus a
+    /// combining acute) produce identical bytes. Unicode's stability policy
+    /// fixes the NFC form of assigned characters; unassigned code points pass
+    /// through unchanged.
+    fn normalize_utf8(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let text = std::str::from_utf8(input).map_err(|e| format!("Invalid UTF-8 input: {}", e))?;
+        Ok(text.nfc().collect::<String>().into_bytes())
+    }
+
     /// Deterministic Fibonacci computation
     fn fibonacci_computation(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         if input.is_empty() {
             return Err("No input provided for Fibonacci computation".into());
         }
-        
+
         let n = input[0] as usize;
-        if n > 93 {  // Prevent overflow for u64
+        if n > 93 {
+            // Prevent overflow for u64
             return Err("Fibonacci number too large".into());
         }
-        
+
         let result = self.fibonacci(n);
         Ok(result.to_be_bytes().to_vec())
     }
-    
+
+    /// F(0) through F(n) as concatenated 8-byte big-endian values
+    ///
+    /// Additions are checked, so an `n` whose terms would overflow `u64` is
+    /// rejected rather than producing wrapped values.
+    fn fibonacci_sequence(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let n = *input
+            .first()
+            .ok_or("No input provided for Fibonacci sequence")? as usize;
+
+        let mut terms = vec![0u64, 1];
+        for k in 2..=n {
+            let next = terms[k - 1]
+                .checked_add(terms[k - 2])
+                .ok_or("Fibonacci sequence overflows u64")?;
+            terms.push(next);
+        }
+        terms.truncate(n + 1);
+
+        Ok(terms.iter().flat_map(|term| term.to_be_bytes()).collect())
+    }
+
     /// Compute Fibonacci number deterministically
     fn fibonacci(&self, n: usize) -> u64 {
         if n <= 1 {
             return n as u64;
         }
-        
+
         let mut a = 0u64;
         let mut b = 1u64;
-        
+
         for _ in 2..=n {
             let temp = a.wrapping_add(b);
             a = b;
             b = temp;
         }
-        
+
         b
     }
-    
+
+    /// Prime factorization of a big-endian `u64` by trial division
+    ///
+    /// Output is `(prime, exponent)` pairs as 8-byte big-endian values with
+    /// primes ascending; 1 factors to the empty list. Values above
+    /// `MAX_FACTORIZE_INPUT` are rejected so the running time stays bounded.
+    fn factorize(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if input.len() != 8 {
+            return Err(format!("Factorize input must be 8 bytes, got {}", input.len()).into());
+        }
+        let mut n = read_u64_be(input, 0)?;
+        if n == 0 {
+            return Err("Zero has no prime factorization".into());
+        }
+        if n > MAX_FACTORIZE_INPUT {
+            return Err(format!(
+                "Factorize input {} exceeds the limit of {}",
+                n, MAX_FACTORIZE_INPUT
+            )
+            .into());
+        }
+
+        let mut output = Vec::new();
+        let mut divisor = 2u64;
+        while divisor * divisor <= n {
+            let mut exponent = 0u64;
+            while n % divisor == 0 {
+                n /= divisor;
+                exponent += 1;
+            }
+            if exponent > 0 {
+                output.extend_from_slice(&divisor.to_be_bytes());
+                output.extend_from_slice(&exponent.to_be_bytes());
+            }
+            divisor += if divisor == 2 { 1 } else { 2 };
+        }
+        // Whatever remains has no factor up to its square root
+        if n > 1 {
+            output.extend_from_slice(&n.to_be_bytes());
+            output.extend_from_slice(&1u64.to_be_bytes());
+        }
+        Ok(output)
+    }
+
     /// Generate computation key for caching
-    fn generate_computation_key(&self, operation: &str, input: &[u8]) -> String {
+    ///
+    /// Operation and input are length-prefixed as in `proof_v2`, so splitting
+    /// the same bytes differently between them gives a different key. A
+    /// non-empty context follows the seed, also length-prefixed.
+    fn generate_computation_key(&self, operation: &str, input: &[u8], context: &[u8]) -> String {
         let mut hasher = Sha256::new();
-        hasher.update(operation.as_bytes());
-        hasher.update(input);
+        for field in [operation.as_bytes(), input] {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field);
+        }
         hasher.update(self.seed.to_be_bytes());
+        if !context.is_empty() {
+            hasher.update((context.len() as u64).to_be_bytes());
+            hasher.update(context);
+        }
         hex::encode(hasher.finalize())
     }
-    
-    /// Generate cryptographic proof of computation
-    fn generate_proof(&self, result: &[u8], operation: &str, input: &[u8]) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(b"DETERMINISTIC_PROOF_V1");
-        hasher.update(operation.as_bytes());
-        hasher.update(input);
-        hasher.update(result);
-        hasher.update(self.seed.to_be_bytes());
-        hex::encode(hasher.finalize())
+
+    /// Generate cryptographic proof of computation under this engine's tag and seed
+    fn generate_proof(
+        &self,
+        result: &[u8],
+        operation: &str,
+        input: &[u8],
+        context: &[u8],
+    ) -> String {
+        let operation = proof_operation(operation, self.hash_truncation);
+        proof_v2(
+            &self.domain_tag,
+            &operation,
+            input,
+            result,
+            self.seed,
+            context,
+        )
+    }
+
+    /// Iterate over the keys of all cached computations
+    pub fn cached_keys(&self) -> impl Iterator<Item = &str> {
+        self.computation_cache.keys().map(String::as_str)
+    }
+
+    /// Look up a cached result by its computation key
+    pub fn get_cached(&self, key: &str) -> Option<&ComputationResult> {
+        self.computation_cache.get(key).map(|entry| &entry.result)
+    }
+
+    /// Drop every cached result produced by `operation`, returning how many were removed
+    pub fn invalidate_operation(&mut self, operation: &str) -> usize {
+        let before = self.computation_cache.len();
+        let cache_bytes = &mut self.cache_bytes;
+        self.computation_cache.retain(|_, entry| {
+            let keep = entry.operation != operation;
+            if !keep {
+                *cache_bytes -= entry.result.heap_size();
+            }
+            keep
+        });
+        let cache = &self.computation_cache;
+        self.cache_order.retain(|key| cache.contains_key(key));
+        before - self.computation_cache.len()
     }
-    
+
+    /// Total heap bytes held by cached results
+    pub fn cache_bytes(&self) -> usize {
+        self.cache_bytes
+    }
+
+    /// Cache an entry, then evict the oldest entries until within the byte limit
+    fn insert_cached(&mut self, key: String, entry: CacheEntry) {
+        let size = entry.result.heap_size();
+        if self.max_cache_bytes.is_some_and(|max| size > max) {
+            return;
+        }
+        self.cache_bytes += size;
+        if let Some(previous) = self.computation_cache.insert(key.clone(), entry) {
+            self.cache_bytes -= previous.result.heap_size();
+        } else {
+            self.cache_order.push_back(key);
+        }
+        self.evict_to_byte_limit();
+    }
+
+    /// Evict oldest cached results while the byte limit is exceeded
+    fn evict_to_byte_limit(&mut self) {
+        let Some(max_bytes) = self.max_cache_bytes else {
+            return;
+        };
+        while self.cache_bytes > max_bytes {
+            let Some(key) = self.cache_order.pop_front() else {
+                break;
+            };
+            if let Some(entry) = self.computation_cache.remove(&key) {
+                self.cache_bytes -= entry.result.heap_size();
+            }
+        }
+    }
+
+    /// Commit to a result value without revealing it
+    ///
+    /// The opening randomness is drawn from the engine's RNG, so a given seed
+    /// produces the same sequence of commitments. Check an opening with
+    /// `commitment::verify_commitment`.
+    pub fn commit_result(&mut self, value_bytes: &[u8]) -> (Commitment, Opening) {
+        let mut opening = Opening {
+            randomness: [0u8; 32],
+        };
+        self.rng.fill(&mut opening.randomness);
+        (commitment::commit(value_bytes, &opening), opening)
+    }
+
     /// Verify computation result
-    pub fn verify_result(&self, result: &ComputationResult, operation: &str, input: &[u8]) -> Result<bool, Box<dyn std::error::Error>> {
-        // Verify proof hash
-        let expected_proof = self.generate_proof(&result.value, operation, input);
-        if expected_proof != result.proof_hash {
-            return Ok(false);
-        }
-        
-        // Verify signature
-        if !self.crypto.verify_signature(&result.value, &result.proof_hash, &result.verification_signature)? {
-            return Ok(false);
-        }
-        
-        // Verify seed matches
-        if result.seed != self.seed {
-            return Ok(false);
-        }
-        
-        Ok(true)
-    }
-    
+    pub fn verify_result(
+        &self,
+        result: &ComputationResult,
+        operation: &str,
+        input: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        self.verify_result_with_context(result, operation, input, &[])
+    }
+
+    /// Verify a result and require its timestamp within `max_age` of this engine's clock
+    ///
+    /// Timestamps further than `max_age` in the past or the future are
+    /// rejected, so clock skew between producer and verifier must fit inside
+    /// the window. The timestamp is not covered by the proof or signature: this
+    /// screens out stale or replayed results from honest producers and caches,
+    /// but cannot stop a holder of a valid result from rewriting its timestamp.
+    pub fn verify_result_fresh(
+        &self,
+        result: &ComputationResult,
+        operation: &str,
+        input: &[u8],
+        max_age: Duration,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let age = self.clock.now().abs_diff(result.timestamp);
+        Ok(age <= max_age.as_secs() && self.verify_result(result, operation, input)?)
+    }
+
+    /// Verify a result produced by `compute_over_digest`
+    pub fn verify_digest_result(
+        &self,
+        result: &ComputationResult,
+        operation: &str,
+        input_digest: &[u8; 32],
+        original_len: u64,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let (proof_operation, proof_input) =
+            pre_digest_proof_fields(operation, input_digest, original_len);
+        self.verify_result(result, &proof_operation, &proof_input)
+    }
+
+    /// Verify a result produced by `compute_with_context` under `context`
+    pub fn verify_result_with_context(
+        &self,
+        result: &ComputationResult,
+        operation: &str,
+        input: &[u8],
+        context: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        // Proof hash, then signature, then seed; each check short-circuits the next
+        let expected_proof = self.generate_proof(&result.value, operation, input, context);
+        let verified = expected_proof == result.proof_hash
+            && verify_signed_result(
+                result.proof_hash.as_bytes(),
+                &result.verification_signature,
+                self.signing_key.public_key(),
+            )
+            && result.seed == self.seed;
+
+        tracing::debug!(operation, verified, proof = %result.proof_hash, "result verification");
+        Ok(verified)
+    }
+
+    /// Verify a result signed by another engine, given only its public key
+    ///
+    /// The proof is re-derived under the seed the result carries instead of
+    /// this engine's, with this engine's domain tag and hash truncation, and
+    /// the signature is checked against `public_key`. No seed is shared: the
+    /// key alone ties the result to its producer.
+    pub fn verify_result_with_key(
+        &self,
+        result: &ComputationResult,
+        operation: &str,
+        input: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let operation = proof_operation(operation, self.hash_truncation);
+        let expected_proof = proof_v2(
+            &self.domain_tag,
+            &operation,
+            input,
+            &result.value,
+            result.seed,
+            &[],
+        );
+        let verified = expected_proof == result.proof_hash
+            && verify_signed_result(
+                result.proof_hash.as_bytes(),
+                &result.verification_signature,
+                public_key,
+            );
+
+        tracing::debug!(operation = %operation, verified, proof = %result.proof_hash, "keyed result verification");
+        Ok(verified)
+    }
+
     /// Export comprehensive audit report
     pub fn export_audit_report(&self) -> String {
-        serde_json::to_string_pretty(&serde_json::json!({
-            "deterministic_engine": {
-                "version": "1.0.0",
-                "seed": self.seed,
-                "total_computations": self.computation_cache.len(),
-                "cached_results": self.computation_cache.len(),
-                "audit_logs": self.audit_logger.get_summary(),
-                "timestamp": SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-                "reproducibility_guarantee": true,
-                "formal_verification_enabled": true,
-                "cryptographic_security": "ChaCha20 + SHA-256"
-            }
-        })).unwrap_or_else(|_| "Error generating report".to_string())
+        let mut buffer = Vec::new();
+        match self.write_audit_report(&mut buffer) {
+            Ok(()) => {
+                String::from_utf8(buffer).unwrap_or_else(|_| "Error generating report".to_string())
+            }
+            Err(_) => "Error generating report".to_string(),
+        }
     }
-}
 
-/// Run comprehensive benchmarks
-pub fn run_benchmarks() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🚀 Deterministic Computation Engine Benchmarks");
-    println!("==============================================\n");
-    
-    let mut engine = DeterministicEngine::new(Some(12345));
-    let benchmark = PerformanceBenchmark::new();
-    
-    // Hash benchmarks
-    println!("1. Hash Computation Benchmarks:");
-    for size in [1024, 4096, 16384, 65536] {
-        let data = vec![0u8; size];
-        let start = std::time::Instant::now();
-        let result = engine.compute("hash", &data)?;
-        let duration = start.elapsed();
-        
-        println!("   {} bytes: {:?} ({:.2} MB/s)", 
-                size, duration, 
-                (size as f64 / 1_048_576.0) / duration.as_secs_f64());
-        
-        // Verify determinism
-        let result2 = engine.compute("hash", &data)?;
-        assert_eq!(result.value, result2.value, "Determinism failure!");
-    }
-    
-    println!("\n2. Matrix Computation Benchmarks:");
-    for size in [8, 16, 32] {
-        let mut matrix_data = Vec::new();
-        matrix_data.extend_from_slice(&(size as u32).to_be_bytes());
-        matrix_data.extend_from_slice(&(size as u32).to_be_bytes());
-        
-        for i in 0..(size * size) {
-            matrix_data.extend_from_slice(&(i as f64).to_be_bytes());
+    /// Stream the audit report as pretty-printed JSON into `writer`
+    ///
+    /// The report is serialized field by field straight into the writer, so
+    /// no intermediate JSON tree or string is built; only the per-operation
+    /// timing means are collected first. Fields are declared in key order,
+    /// matching the sorted keys of the original `json!`-built report.
+    pub fn write_audit_report(&self, writer: impl std::io::Write) -> std::io::Result<()> {
+        /// Report body; field order is the serialized key order
+        #[derive(Serialize)]
+        struct AuditReport<'a, S> {
+            audit_logs: S,
+            cached_results: usize,
+            cryptographic_security: &'static str,
+            formal_verification_enabled: bool,
+            operation_timings_ns: BTreeMap<&'a str, u64>,
+            reproducibility_guarantee: bool,
+            seed: u64,
+            timestamp: u64,
+            total_computations: usize,
+            version: &'static str,
+        }
+
+        #[derive(Serialize)]
+        struct Document<'a, S> {
+            deterministic_engine: AuditReport<'a, S>,
         }
-        
-        let start = std::time::Instant::now();
-        let _result = engine.compute("matrix_multiply", &matrix_data)?;
-        let duration = start.elapsed();
-        
-        println!("   {}x{} matrix: {:?}", size, size, duration);
+
+        // Mean execution time per operation, in a stable key order
+        let mut timing_totals: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+        for entry in self.computation_cache.values() {
+            let (total, count) = timing_totals
+                .entry(entry.operation.as_str())
+                .or_insert((0, 0));
+            *total += entry.result.execution_time_ns;
+            *count += 1;
+        }
+        let operation_timings: BTreeMap<&str, u64> = timing_totals
+            .into_iter()
+            .map(|(op, (total, count))| (op, total / count))
+            .collect();
+
+        let document = Document {
+            deterministic_engine: AuditReport {
+                audit_logs: self.audit_logger.get_summary(),
+                cached_results: self.computation_cache.len(),
+                cryptographic_security: "ChaCha20 + SHA-256",
+                formal_verification_enabled: true,
+                operation_timings_ns: operation_timings,
+                reproducibility_guarantee: true,
+                seed: self.seed,
+                timestamp: self.clock.now(),
+                total_computations: self.computation_cache.len(),
+                version: "1.0.0",
+            },
+        };
+        serde_json::to_writer_pretty(writer, &document).map_err(std::io::Error::from)
     }
-    
+}
+
+/// Outcome of a single benchmark determinism check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeterminismCheck {
+    pub operation: String,
+    pub input_size: usize,
+    pub passed: bool,
+}
+
+/// Collects determinism checks so every case is reported, not just the first failure
+#[derive(Debug, Default)]
+pub struct DeterminismRecorder {
+    checks: Vec<DeterminismCheck>,
+    keep_going: bool,
+}
+
+impl DeterminismRecorder {
+    /// Create a recorder; with `keep_going`, failures are recorded without aborting
+    pub fn new(keep_going: bool) -> Self {
+        Self {
+            checks: Vec::new(),
+            keep_going,
+        }
+    }
+
+    /// Record a check, returning an error on failure unless in keep-going mode
+    pub fn record(
+        &mut self,
+        operation: &str,
+        input_size: usize,
+        passed: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.checks.push(DeterminismCheck {
+            operation: operation.to_string(),
+            input_size,
+            passed,
+        });
+        if !passed && !self.keep_going {
+            return Err(
+                format!("Determinism failure: {} on {} bytes", operation, input_size).into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Number of recorded checks that failed
+    pub fn failures(&self) -> usize {
+        self.checks.iter().filter(|check| !check.passed).count()
+    }
+
+    /// Write every recorded check as one JSON object per line
+    pub fn write_jsonl(
+        &self,
+        mut writer: impl std::io::Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for check in &self.checks {
+            serde_json::to_writer(&mut writer, check)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Cases and iteration counts for `run_benchmarks`
+///
+/// `Default` is the full suite; trim the lists for a quick smoke run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkConfig {
+    /// Input sizes in bytes for the hash benchmark
+    pub hash_sizes: Vec<usize>,
+    /// Side lengths of the square matrices to transpose
+    pub matrix_sizes: Vec<usize>,
+    /// Values of `n` for the Fibonacci benchmark
+    pub fibonacci_ns: Vec<u8>,
+    /// Hashes per variant in the small-input throughput comparison
+    pub small_hash_iterations: usize,
+    /// Samples collected for the timing-stability comparison
+    pub timing_samples: usize,
+    /// `(degree, suffix bytes)` pairs for the polynomial benchmark
+    pub polynomial_cases: Vec<(u32, usize)>,
+    /// Untimed runs before each case's measured iterations
+    pub warmup_iterations: usize,
+    /// Timed runs averaged for each case, on both the cache-miss and cache-hit paths
+    pub iterations: usize,
+    /// Directory receiving the determinism log and the audit report
+    pub output_dir: PathBuf,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self {
+            hash_sizes: vec![1024, 4096, 16384, 65536],
+            matrix_sizes: vec![8, 16, 32],
+            fibonacci_ns: vec![10, 20, 30, 40, 50],
+            small_hash_iterations: 200_000,
+            timing_samples: 32,
+            polynomial_cases: vec![(10, 4096), (1000, 64), (1000, 4096)],
+            warmup_iterations: 2,
+            iterations: 10,
+            output_dir: PathBuf::from("."),
+        }
+    }
+}
+
+/// Mean and population standard deviation of repeated timings
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct TimingStats {
+    pub mean_ns: f64,
+    pub stddev_ns: f64,
+}
+
+impl std::fmt::Display for TimingStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0} +/- {:.0} ns", self.mean_ns, self.stddev_ns)
+    }
+}
+
+/// Timings of a single benchmark case
+///
+/// The cache-miss path recomputes on every iteration (caching disabled); the
+/// cache-hit path repeats an input that is already cached.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchmarkCase {
+    pub operation: String,
+    /// Human-readable case parameters, e.g. `"1024 bytes"`
+    pub label: String,
+    pub cache_miss: TimingStats,
+    pub cache_hit: TimingStats,
+}
+
+/// Time one case on both cache paths, after `config.warmup_iterations` untimed runs
+fn measure_case(
+    cached: &mut DeterministicEngine,
+    uncached: &mut DeterministicEngine,
+    operation: &str,
+    label: String,
+    input: &[u8],
+    config: &BenchmarkConfig,
+) -> Result<BenchmarkCase, Box<dyn std::error::Error>> {
+    let time_path =
+        |engine: &mut DeterministicEngine| -> Result<TimingStats, Box<dyn std::error::Error>> {
+            for _ in 0..config.warmup_iterations {
+                engine.compute(operation, input)?;
+            }
+            let mut samples = Vec::with_capacity(config.iterations);
+            for _ in 0..config.iterations {
+                let start = std::time::Instant::now();
+                std::hint::black_box(engine.compute(operation, input)?);
+                samples.push(start.elapsed().as_nanos() as f64);
+            }
+            let (mean_ns, stddev_ns) = mean_and_stddev(&samples);
+            Ok(TimingStats { mean_ns, stddev_ns })
+        };
+
+    let cache_miss = time_path(uncached)?;
+    // Prime the cache so every timed iteration is a hit, even without warmup
+    cached.compute(operation, input)?;
+    let cache_hit = time_path(cached)?;
+
+    Ok(BenchmarkCase {
+        operation: operation.to_string(),
+        label,
+        cache_miss,
+        cache_hit,
+    })
+}
+
+/// Run comprehensive benchmarks
+///
+/// Determinism checks are written to `determinism_checks.jsonl` in the
+/// configured output directory. With `keep_going`, a failed check is recorded
+/// and the run continues, returning an error only once every case has been
+/// reported. Returns the timing of every per-operation case.
+pub fn run_benchmarks(
+    config: &BenchmarkConfig,
+    keep_going: bool,
+) -> Result<Vec<BenchmarkCase>, Box<dyn std::error::Error>> {
+    let mut reporter = Reporter::stdout();
+    reporter.status(Symbol::Start, "Deterministic Computation Engine Benchmarks")?;
+    println!("==============================================\n");
+
+    let signing_key = ephemeral_signing_key();
+    let mut engine = DeterministicEngine::new(Some(12345), &signing_key);
+    let mut uncached = DeterministicEngine::new(Some(12345), &signing_key).with_caching(false);
+    let benchmark = PerformanceBenchmark::new();
+    let mut recorder = DeterminismRecorder::new(keep_going);
+    let mut cases = Vec::new();
+    let checks_path = config.output_dir.join("determinism_checks.jsonl");
+
+    // Hash benchmarks
+    println!("1. Hash Computation Benchmarks:");
+    for &size in &config.hash_sizes {
+        let data = vec![0u8; size];
+        let case = measure_case(
+            &mut engine,
+            &mut uncached,
+            "hash",
+            format!("{} bytes", size),
+            &data,
+            config,
+        )?;
+
+        println!(
+            "   {} bytes: {} miss ({:.2} MB/s), {} hit",
+            size,
+            case.cache_miss,
+            (size as f64 / 1_048_576.0) / (case.cache_miss.mean_ns / 1e9),
+            case.cache_hit
+        );
+        cases.push(case);
+
+        // Verify determinism across two independent recomputations
+        let result = uncached.compute("hash", &data)?;
+        let result2 = uncached.compute("hash", &data)?;
+        let passed = result.value == result2.value;
+        if let Err(e) = recorder.record("hash", size, passed) {
+            recorder.write_jsonl(std::fs::File::create(&checks_path)?)?;
+            return Err(e);
+        }
+    }
+
+    println!("\n2. Matrix Computation Benchmarks:");
+    for &size in &config.matrix_sizes {
+        let mut matrix_data = Vec::new();
+        matrix_data.extend_from_slice(&(size as u32).to_be_bytes());
+        matrix_data.extend_from_slice(&(size as u32).to_be_bytes());
+
+        for i in 0..(size * size) {
+            matrix_data.extend_from_slice(&(i as f64).to_be_bytes());
+        }
+
+        let label = format!("{}x{}", size, size);
+        let case = measure_case(
+            &mut engine,
+            &mut uncached,
+            "matrix_multiply",
+            label,
+            &matrix_data,
+            config,
+        )?;
+
+        println!(
+            "   {}x{} matrix: {} miss, {} hit",
+            size, size, case.cache_miss, case.cache_hit
+        );
+        cases.push(case);
+    }
+
     println!("\n3. Fibonacci Benchmarks:");
-    for n in [10, 20, 30, 40, 50] {
+    for &n in &config.fibonacci_ns {
         let input = vec![n];
-        let start = std::time::Instant::now();
+        let case = measure_case(
+            &mut engine,
+            &mut uncached,
+            "fibonacci",
+            format!("F({})", n),
+            &input,
+            config,
+        )?;
         let result = engine.compute("fibonacci", &input)?;
-        let duration = start.elapsed();
-        
-        let fib_value = u64::from_be_bytes([
-            result.value[0], result.value[1], result.value[2], result.value[3],
-            result.value[4], result.value[5], result.value[6], result.value[7],
-        ]);
-        
-        println!("   F({}) = {} in {:?}", n, fib_value, duration);
-    }
-    
-    println!("\n✅ All benchmarks completed successfully!");
-    println!("📊 Total cached computations: {}", engine.computation_cache.len());
-    
+
+        let fib_value = result.as_u64().ok_or("Fibonacci result is not 8 bytes")?;
+
+        println!(
+            "   F({}) = {} in {} miss, {} hit",
+            n, fib_value, case.cache_miss, case.cache_hit
+        );
+        cases.push(case);
+    }
+
+    println!("\n4. Small-Input Hash Throughput (seeded state reuse):");
+    let small_input = [0xA5u8; 32];
+    let iterations = config.small_hash_iterations;
+    let mut seeded = Sha256::new();
+    seeded.update(engine.seed.to_be_bytes());
+    let start = std::time::Instant::now();
+    for _ in 0..iterations {
+        let mut hasher = Sha256::new();
+        hasher.update(engine.seed.to_be_bytes());
+        hasher.update(small_input);
+        std::hint::black_box(hasher.finalize());
+    }
+    let naive = start.elapsed();
+    let start = std::time::Instant::now();
+    for _ in 0..iterations {
+        let mut hasher = std::hint::black_box(&seeded).clone();
+        hasher.update(small_input);
+        std::hint::black_box(hasher.finalize());
+    }
+    let cached = start.elapsed();
+    println!(
+        "   fresh hasher:  {:.0} hashes/s",
+        iterations as f64 / naive.as_secs_f64()
+    );
+    println!(
+        "   seeded clone:  {:.0} hashes/s",
+        iterations as f64 / cached.as_secs_f64()
+    );
+
+    println!("\n5. Timing Stability (wall-clock vs CPU time):");
+    let mut timing_engine =
+        DeterministicEngine::new(Some(12345), &signing_key).with_cpu_time_measurement(true);
+    let mut wall_samples = Vec::new();
+    let mut cpu_samples = Vec::new();
+    for i in 0..config.timing_samples as u64 {
+        // Vary the input so every run is a cache miss
+        let mut data = vec![0u8; 65536];
+        write_u64_be(&mut data, 0, i)?;
+        let result = timing_engine.compute("hash", &data)?;
+        wall_samples.push(result.execution_time_ns as f64);
+        if let Some(cpu_ns) = result.cpu_time_ns {
+            cpu_samples.push(cpu_ns as f64);
+        }
+    }
+    println!(
+        "   wall-clock coefficient of variation: {:.3}",
+        coefficient_of_variation(&wall_samples)
+    );
+    if cpu_samples.is_empty() {
+        println!("   CPU time unavailable on this platform");
+    } else {
+        println!(
+            "   CPU time coefficient of variation:   {:.3}",
+            coefficient_of_variation(&cpu_samples)
+        );
+    }
+
+    println!("\n6. High-Degree Polynomial Evaluation:");
+    for &(degree, suffix_len) in &config.polynomial_cases {
+        let mut input = Vec::new();
+        input.extend_from_slice(&degree.to_be_bytes());
+        input.extend_from_slice(&1.0001f64.to_be_bytes());
+        input.resize(12 + suffix_len, 0x5A);
+        let label = format!("degree {} over {} bytes", degree, suffix_len);
+        let case = measure_case(
+            &mut engine,
+            &mut uncached,
+            "polynomial_eval",
+            label,
+            &input,
+            config,
+        )?;
+
+        println!(
+            "   degree {} over {} suffix bytes: {} miss, {} hit",
+            degree, suffix_len, case.cache_miss, case.cache_hit
+        );
+        cases.push(case);
+    }
+
+    recorder.write_jsonl(std::fs::File::create(&checks_path)?)?;
+    if recorder.failures() > 0 {
+        return Err(format!(
+            "{} determinism checks failed; see determinism_checks.jsonl",
+            recorder.failures()
+        )
+        .into());
+    }
+
+    println!();
+    reporter.status(Symbol::Done, "All benchmarks completed successfully!")?;
+    reporter.status(
+        Symbol::Stats,
+        format_args!(
+            "Total cached computations: {}",
+            engine.computation_cache.len()
+        ),
+    )?;
+
     // Export audit report
     let audit_report = engine.export_audit_report();
-    std::fs::write("deterministic_audit.json", audit_report)?;
-    println!("📋 Audit report exported to deterministic_audit.json");
-    
-    Ok(())
+    std::fs::write(
+        config.output_dir.join("deterministic_audit.json"),
+        audit_report,
+    )?;
+    reporter.status(
+        Symbol::Export,
+        "Audit report exported to deterministic_audit.json",
+    )?;
+
+    Ok(cases)
+}
+
+/// Standard deviation divided by mean; lower means steadier timings
+fn coefficient_of_variation(samples: &[f64]) -> f64 {
+    let (mean, stddev) = mean_and_stddev(samples);
+    if mean == 0.0 {
+        return 0.0;
+    }
+    stddev / mean
+}
+
+/// Mean and population standard deviation; both zero for no samples
+fn mean_and_stddev(samples: &[f64]) -> (f64, f64) {
+    if samples.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+    (mean, variance.sqrt())
+}
+
+/// Fresh Ed25519 signing secret for runs whose signatures are never published
+///
+/// The demo, benchmarks and self-test only compare values, so each run signs
+/// under a throwaway key from the OS-seeded thread RNG.
+pub fn ephemeral_signing_key() -> [u8; 32] {
+    rand::random()
+}
+
+/// Operation name and input that a pre-digest proof is taken over
+///
+/// `@` never appears in a built-in operation name, so these proofs cannot
+/// coincide with any full-input proof.
+fn pre_digest_proof_fields(
+    operation: &str,
+    input_digest: &[u8; 32],
+    original_len: u64,
+) -> (String, Vec<u8>) {
+    let mut input = input_digest.to_vec();
+    input.extend_from_slice(&original_len.to_be_bytes());
+    (format!("{}@sha256-digest", operation), input)
+}
+
+/// Algorithm R: keeps the first `k` values, then replaces a random slot
+///
+/// Replacement indices are sampled as u64 for portability.
+fn reservoir_sample(
+    values: impl IntoIterator<Item = u64>,
+    k: usize,
+    rng: &mut ChaCha20Rng,
+) -> Vec<u64> {
+    let mut values = values.into_iter();
+    let mut reservoir: Vec<u64> = values.by_ref().take(k).collect();
+    for (i, value) in values.enumerate() {
+        let j = rng.gen_range(0..=(k + i) as u64) as usize;
+        if j < k {
+            reservoir[j] = value;
+        }
+    }
+    reservoir
+}
+
+/// `byte_diff` output under construction, merging adjacent ops
+#[derive(Default)]
+struct DiffScript {
+    output: Vec<u8>,
+    copied: usize,
+    deleted: usize,
+    inserted: Vec<u8>,
+}
+
+impl DiffScript {
+    fn copy(&mut self, len: usize) {
+        if len > 0 {
+            self.flush_edits();
+            self.copied += len;
+        }
+    }
+
+    fn delete(&mut self) {
+        self.flush_copy();
+        self.deleted += 1;
+    }
+
+    fn insert(&mut self, byte: u8) {
+        self.flush_copy();
+        self.inserted.push(byte);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.flush_copy();
+        self.flush_edits();
+        self.output
+    }
+
+    fn flush_copy(&mut self) {
+        if self.copied > 0 {
+            self.push_op(DIFF_COPY, self.copied);
+            self.copied = 0;
+        }
+    }
+
+    /// Deletions first, then insertions
+    fn flush_edits(&mut self) {
+        if self.deleted > 0 {
+            self.push_op(DIFF_DELETE, self.deleted);
+            self.deleted = 0;
+        }
+        if !self.inserted.is_empty() {
+            self.push_op(DIFF_INSERT, self.inserted.len());
+            self.output.append(&mut self.inserted);
+        }
+    }
+
+    /// Lengths fit in u32 because both strings arrive length-prefixed
+    fn push_op(&mut self, tag: u8, len: usize) {
+        self.output.push(tag);
+        self.output.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+/// Splits a length-prefixed byte string off the front of `input`
+///
+/// On success `input` is advanced past the string.
+fn take_byte_string<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], Box<dyn std::error::Error>> {
+    let len = read_u32_be(input, 0)? as usize;
+    let end = len
+        .checked_add(4)
+        .filter(|&end| end <= input.len())
+        .ok_or("Byte string shorter than its length prefix")?;
+    let bytes = &input[4..end];
+    *input = &input[end..];
+    Ok(bytes)
+}
+
+/// Splits a count-prefixed f64 vector off the front of `input`
+///
+/// On success `input` is advanced past the vector.
+fn take_f64_vector(input: &mut &[u8]) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+    let count = read_u32_be(input, 0)? as usize;
+    let end = count
+        .checked_mul(8)
+        .and_then(|body_len| body_len.checked_add(4))
+        .filter(|&end| end <= input.len())
+        .ok_or("Vector shorter than its length prefix")?;
+
+    let values = input[4..end]
+        .chunks_exact(8)
+        .map(|chunk| read_f64_be(chunk, 0))
+        .collect::<Result<Vec<_>, ComputeError>>()?;
+    *input = &input[end..];
+    Ok(values)
+}
+
+/// Splits a count-prefixed ascending u64 list off the front of `input`
+///
+/// Duplicates are dropped unless `keep_duplicates` is set; a descending pair
+/// is an error. On success `input` is advanced past the list.
+fn take_sorted_u64_list(
+    input: &mut &[u8],
+    keep_duplicates: bool,
+) -> Result<Vec<u64>, Box<dyn std::error::Error>> {
+    let count = read_u32_be(input, 0)? as usize;
+    let end = count
+        .checked_mul(8)
+        .and_then(|body_len| body_len.checked_add(4))
+        .filter(|&end| end <= input.len())
+        .ok_or("Set operand shorter than its value count")?;
+
+    let mut values: Vec<u64> = Vec::with_capacity(count);
+    for chunk in input[4..end].chunks_exact(8) {
+        let value = read_u64_be(chunk, 0)?;
+        match values.last() {
+            Some(&last) if value < last => return Err("Set operand is not sorted".into()),
+            Some(&last) if value == last && !keep_duplicates => {}
+            _ => values.push(value),
+        }
+    }
+
+    *input = &input[end..];
+    Ok(values)
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔒 Deterministic Computation Engine v1.0.0");
-    println!("High-Performance Cryptographically Secure Platform\n");
-    
+    // Engine diagnostics go to stderr, filtered by RUST_LOG (off by default),
+    // so the status lines on stdout are unchanged
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    // `verify <report-a> <report-b>` compares two audit reports instead of running the demo
+    let args: Vec<String> = std::env::args().collect();
+    let mut reporter = Reporter::stdout();
+    if args.get(1).map(String::as_str) == Some("verify") {
+        let (Some(a), Some(b)) = (args.get(2), args.get(3)) else {
+            return Err("usage: deterministic verify <report-a> <report-b>".into());
+        };
+        let diff = audit_diff::diff_audit_reports(
+            &std::fs::read_to_string(a)?,
+            &std::fs::read_to_string(b)?,
+        )?;
+        if diff.is_match() {
+            reporter.status(Symbol::Pass, "Audit reports match")?;
+            return Ok(());
+        }
+        println!("{:#?}", diff);
+        return Err("Audit reports differ".into());
+    }
+
+    // `verify-v1 <result.json> <operation> <input-hex> [domain-tag]` checks a result stored under the v1 proof scheme
+    if args.get(1).map(String::as_str) == Some("verify-v1") {
+        let (Some(path), Some(operation), Some(input)) = (args.get(2), args.get(3), args.get(4))
+        else {
+            return Err(
+                "usage: deterministic verify-v1 <result.json> <operation> <input-hex> [domain-tag]"
+                    .into(),
+            );
+        };
+        let result: ComputationResult = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let mut verifier = LegacyVerifier::new(result.seed);
+        if let Some(tag) = args.get(5) {
+            verifier = verifier.with_domain_tag(tag.as_bytes());
+        }
+        if verifier.verify(&result, operation, &hex::decode(input)?)? {
+            reporter.status(Symbol::Pass, "v1 result verifies")?;
+            return Ok(());
+        }
+        return Err("v1 result does not verify".into());
+    }
+
+    // `self-test` checks this machine reproduces the reference outputs
+    if args.get(1).map(String::as_str) == Some("self-test") {
+        let report = self_test::self_test();
+        for case in &report.cases {
+            reporter.status(
+                if case.passed {
+                    Symbol::Pass
+                } else {
+                    Symbol::Fail
+                },
+                case.operation,
+            )?;
+        }
+        if report.passed() {
+            return Ok(());
+        }
+        let drifted: Vec<_> = report.drifted().collect();
+        return Err(format!("Output drifted for: {}", drifted.join(", ")).into());
+    }
+
+    // `schema <operation>` prints the operation's byte layout as JSON
+    if args.get(1).map(String::as_str) == Some("schema") {
+        let operation = args
+            .get(2)
+            .ok_or("usage: deterministic schema <operation>")?;
+        let schema = schema::operation_schema(operation)
+            .ok_or_else(|| format!("Unknown operation: {}", operation))?;
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+
+    reporter.status(Symbol::Engine, "Deterministic Computation Engine v1.0.0")?;
+    reporter.line("High-Performance Cryptographically Secure Platform\n")?;
+
     // Run demonstration
-    let mut engine = DeterministicEngine::new(Some(2025));
-    
+    let mut engine = DeterministicEngine::new(Some(2025), &ephemeral_signing_key());
+
     // Test basic operations
     let test_data = b"Deterministic computation test data";
     let hash_result = engine.compute("hash", test_data)?;
-    println!("✓ Hash computation: {} bytes -> {} proof", 
-            test_data.len(), hash_result.proof_hash[..16].to_string() + "...");
-    
+    reporter.status(
+        Symbol::Pass,
+        format_args!(
+            "Hash computation: {} bytes -> {} proof",
+            test_data.len(),
+            hash_result.proof_hash[..16].to_string() + "..."
+        ),
+    )?;
+
     let fib_result = engine.compute("fibonacci", &[25])?;
-    let fib_25 = u64::from_be_bytes([
-        fib_result.value[0], fib_result.value[1], fib_result.value[2], fib_result.value[3],
-        fib_result.value[4], fib_result.value[5], fib_result.value[6], fib_result.value[7],
-    ]);
-    println!("✓ Fibonacci(25) = {} with cryptographic proof", fib_25);
-    
+    let fib_25 = fib_result
+        .as_u64()
+        .ok_or("Fibonacci result is not 8 bytes")?;
+    reporter.status(
+        Symbol::Pass,
+        format_args!("Fibonacci(25) = {} with cryptographic proof", fib_25),
+    )?;
+
     // Run full benchmarks
-    println!("\nRunning comprehensive benchmarks...");
-    run_benchmarks()?;
-    
+    reporter.line("\nRunning comprehensive benchmarks...")?;
+    let keep_going = args.iter().any(|arg| arg == "--keep-going");
+    run_benchmarks(&BenchmarkConfig::default(), keep_going)?;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::FixedClock;
+
+    /// Ed25519 secret the test engines sign results with
+    pub(crate) const TEST_SIGNING_KEY: [u8; 32] = [7; 32];
+
+    /// Count-prefixed list input: a u32 BE count, then each value as u64 BE
+    pub(super) fn encode_u64_list(values: &[u64]) -> Vec<u8> {
+        let mut bytes = (values.len() as u32).to_be_bytes().to_vec();
+        for value in values {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// `encode_u64_list` for f64 values
+    pub(super) fn encode_f64_list(values: &[f64]) -> Vec<u8> {
+        let mut bytes = (values.len() as u32).to_be_bytes().to_vec();
+        for value in values {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_fixed_clock_sets_result_timestamp() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_clock(FixedClock(1_700_000_000));
+        let result = engine.compute("hash", b"clock test").unwrap();
+        assert_eq!(result.timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_allow_list_rejects_other_operations() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_allowed_operations(&["hash"]);
+        assert!(engine.compute("hash", b"allowed").is_ok());
+
+        let err = engine.compute("fibonacci", &[10]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ComputeError>(),
+            Some(&ComputeError::OperationNotPermitted(
+                "fibonacci".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sort_preserves_original_order_for_equal_keys() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let records: [(u64, u8); 5] = [(3, b'a'), (1, b'b'), (3, b'c'), (1, b'd'), (2, b'e')];
+
+        let mut input = Vec::new();
+        input.extend_from_slice(&(records.len() as u32).to_be_bytes());
+        input.extend_from_slice(&1u32.to_be_bytes());
+        for (key, payload) in records {
+            input.extend_from_slice(&key.to_be_bytes());
+            input.push(payload);
+        }
+
+        let result = engine.compute("sort", &input).unwrap();
+        let payloads: Vec<u8> = result.value[8..]
+            .chunks_exact(9)
+            .map(|record| record[8])
+            .collect();
+        assert_eq!(payloads, b"bdeac".to_vec());
+    }
+
+    #[test]
+    fn test_strict_seed_policy() {
+        assert_eq!(
+            DeterministicEngine::try_new(Some(0), &TEST_SIGNING_KEY, SeedPolicy::strict()).err(),
+            Some(ComputeError::WeakSeed {
+                bits: 0,
+                min_bits: 32
+            })
+        );
+        assert!(DeterministicEngine::try_new(
+            Some(0x9E37_79B9_7F4A_7C15),
+            &TEST_SIGNING_KEY,
+            SeedPolicy::strict()
+        )
+        .is_ok());
+        assert!(
+            DeterministicEngine::try_new(Some(0), &TEST_SIGNING_KEY, SeedPolicy::Permissive)
+                .is_ok()
+        );
+
+        // A generated seed is full width, so strict mode accepts it
+        assert!(
+            DeterministicEngine::try_new(None, &TEST_SIGNING_KEY, SeedPolicy::strict()).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_cpu_time_recorded_when_enabled() {
+        let data = vec![7u8; 1 << 20];
+
+        let mut engine =
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY).with_cpu_time_measurement(true);
+        let result = engine.compute("hash", &data).unwrap();
+        assert!(result.cpu_time_ns.unwrap() > 0);
+
+        let mut wall_only = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        assert_eq!(wall_only.compute("hash", &data).unwrap().cpu_time_ns, None);
+    }
+
+    #[test]
+    fn test_cached_keys_enumerates_distinct_entries() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        engine.compute("hash", b"one").unwrap();
+        engine.compute("hash", b"two").unwrap();
+        engine.compute("fibonacci", &[12]).unwrap();
+        engine.compute("hash", b"one").unwrap();
+
+        let keys: HashSet<&str> = engine.cached_keys().collect();
+        assert_eq!(keys.len(), 3);
+        for key in keys {
+            assert!(engine.get_cached(key).is_some());
+        }
+        assert!(engine.get_cached("missing").is_none());
+    }
+
+    #[test]
+    fn test_invalidate_operation_removes_only_that_operation() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        engine.compute("hash", b"one").unwrap();
+        engine.compute("hash", b"two").unwrap();
+        engine.compute("fibonacci", &[12]).unwrap();
+
+        assert_eq!(engine.invalidate_operation("hash"), 2);
+        assert_eq!(engine.cached_keys().count(), 1);
+        let remaining = engine.cached_keys().next().unwrap().to_string();
+        assert_eq!(
+            remaining,
+            engine.generate_computation_key("fibonacci", &[12], &[])
+        );
+    }
+
+    #[test]
+    fn test_estimate_cost_for_matrix() {
+        let engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let mut header = Vec::new();
+        header.extend_from_slice(&32u32.to_be_bytes());
+        header.extend_from_slice(&32u32.to_be_bytes());
+
+        let estimate = engine.estimate_cost("matrix_multiply", &header).unwrap();
+        assert_eq!(estimate.output_size, Some(8 + 32 * 32 * 8));
+        assert_eq!(estimate.work_units, 32 * 32);
+        assert!(engine.estimate_cost("unknown", &header).is_err());
+    }
+
+    #[test]
+    fn test_determinism_failures_recorded_as_jsonl() {
+        let mut recorder = DeterminismRecorder::new(true);
+        recorder.record("hash", 1024, true).unwrap();
+        recorder.record("hash", 4096, false).unwrap();
+        recorder.record("hash", 16384, true).unwrap();
+        assert_eq!(recorder.failures(), 1);
+
+        let mut output = Vec::new();
+        recorder.write_jsonl(&mut output).unwrap();
+        let checks: Vec<DeterminismCheck> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(checks.len(), 3);
+        assert_eq!(
+            checks[1],
+            DeterminismCheck {
+                operation: "hash".to_string(),
+                input_size: 4096,
+                passed: false
+            }
+        );
+
+        let mut strict = DeterminismRecorder::new(false);
+        assert!(strict.record("hash", 1024, false).is_err());
+        assert_eq!(strict.failures(), 1);
+    }
+
+    #[test]
+    fn test_result_accessors() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let fib = engine.compute("fibonacci", &[25]).unwrap();
+        assert_eq!(fib.as_u64(), Some(75_025));
+        assert_eq!(fib.value_bytes(), fib.value.as_slice());
+        assert_eq!(fib.proof_hash_str(), fib.proof_hash);
+
+        let hash = engine.compute("hash", b"not a number").unwrap();
+        assert_eq!(hash.as_u64(), None);
+    }
+
+    #[test]
+    fn test_cache_byte_limit_evicts_oldest_results() {
+        let probe = DeterministicEngine::new(Some(7), &TEST_SIGNING_KEY)
+            .compute("hash", b"probe")
+            .unwrap();
+        // 32-byte digest plus 64 hex characters of proof hash
+        let result_size = probe.heap_size();
+        assert_eq!(result_size, 32 + 64);
+
+        let mut engine = DeterministicEngine::new(Some(7), &TEST_SIGNING_KEY)
+            .with_cache_byte_limit(2 * result_size);
+        engine.compute("hash", b"first").unwrap();
+        engine.compute("hash", b"second").unwrap();
+        assert_eq!(engine.cache_bytes(), 2 * result_size);
+        assert_eq!(engine.cached_keys().count(), 2);
+
+        // A third result pushes the cache over budget and evicts the oldest
+        engine.compute("hash", b"third").unwrap();
+        assert_eq!(engine.cache_bytes(), 2 * result_size);
+        assert!(engine
+            .get_cached(&engine.generate_computation_key("hash", b"first", &[]))
+            .is_none());
+        assert!(engine
+            .get_cached(&engine.generate_computation_key("hash", b"second", &[]))
+            .is_some());
+        assert!(engine
+            .get_cached(&engine.generate_computation_key("hash", b"third", &[]))
+            .is_some());
+
+        assert_eq!(engine.invalidate_operation("hash"), 2);
+        assert_eq!(engine.cache_bytes(), 0);
+    }
+
+    #[test]
+    fn test_proof_rejected_under_different_domain_tag() {
+        let mut engine_a =
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY).with_domain_tag("deployment-a");
+        let engine_b =
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY).with_domain_tag("deployment-b");
+
+        let result = engine_a.compute("hash", b"payload").unwrap();
+        assert!(engine_a.verify_result(&result, "hash", b"payload").unwrap());
+        assert!(!engine_b.verify_result(&result, "hash", b"payload").unwrap());
+
+        // Naming the default tag explicitly matches an unconfigured engine
+        let mut default_engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let result = default_engine.compute("hash", b"payload").unwrap();
+        let tagged = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_domain_tag(DEFAULT_PROOF_DOMAIN_TAG);
+        assert!(tagged.verify_result(&result, "hash", b"payload").unwrap());
+    }
+
+    #[test]
+    fn test_polynomial_coefficients_match_reference_derivation() {
+        let mut input = Vec::new();
+        input.extend_from_slice(&1000u32.to_be_bytes());
+        input.extend_from_slice(&0.5f64.to_be_bytes());
+        input.extend_from_slice(&[0x5A; 256]);
+
+        // Independent re-derivation: one fresh SHA-256 per coefficient
+        let seed = 99u64;
+        let mut expected = 0.0;
+        let mut x_power = 1.0;
+        for i in 0..=1000u64 {
+            let mut hasher = Sha256::new();
+            hasher.update((seed + i).to_be_bytes());
+            hasher.update(&input[12..]);
+            let hash = hasher.finalize();
+            let coefficient = f64::from_be_bytes(hash[..8].try_into().unwrap()) / 1e18;
+            expected += coefficient * x_power;
+            x_power *= 0.5;
+        }
+
+        let result = DeterministicEngine::new(Some(seed), &TEST_SIGNING_KEY)
+            .compute("polynomial_eval", &input)
+            .unwrap();
+        assert_eq!(result.value, expected.to_be_bytes());
+    }
+
+    #[test]
+    fn test_blake3_hasher_drives_hash_operation() {
+        use deterministic_engine::hasher::Blake3Hasher;
+
+        let mut engine = DeterministicEngine::new(Some(5), &TEST_SIGNING_KEY)
+            .with_hasher(Blake3Hasher::default());
+        let result = engine.compute("hash", b"payload").unwrap();
+
+        let mut expected = blake3::Hasher::new();
+        expected.update(&5u64.to_be_bytes());
+        expected.update(b"payload");
+        assert_eq!(result.value, expected.finalize().as_bytes().to_vec());
+        assert!(engine.verify_result(&result, "hash", b"payload").unwrap());
+        assert_eq!(
+            engine
+                .estimate_cost("hash", b"payload")
+                .unwrap()
+                .output_size,
+            Some(32)
+        );
+
+        // The default engine still produces SHA-256 digests
+        let sha = DeterministicEngine::new(Some(5), &TEST_SIGNING_KEY)
+            .compute("hash", b"payload")
+            .unwrap();
+        assert_ne!(sha.value, result.value);
+    }
+
+    #[test]
+    fn test_matrix_dimension_overflow_is_rejected() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let mut input = Vec::new();
+        input.extend_from_slice(&u32::MAX.to_be_bytes());
+        input.extend_from_slice(&u32::MAX.to_be_bytes());
+        input.extend_from_slice(&[0u8; 64]);
+
+        let err = engine.compute("matrix_multiply", &input).unwrap_err();
+        assert_eq!(err.to_string(), "Matrix dimensions overflow");
+
+        // Large but representable dimensions fail the length check instead
+        input[4..8].copy_from_slice(&1u32.to_be_bytes());
+        let err = engine.compute("matrix_multiply", &input).unwrap_err();
+        assert_eq!(err.to_string(), "Insufficient data for matrix");
+    }
+
+    #[test]
+    fn test_shuffle_is_reproducible_permutation() {
+        let values: Vec<u64> = (0..50).map(|i| i * 7 % 13).collect();
+        let input = encode_u64_list(&values);
+
+        let decode = |bytes: &[u8]| -> Vec<u64> {
+            bytes[4..]
+                .chunks_exact(8)
+                .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+                .collect()
+        };
+
+        let first = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .compute("shuffle", &input)
+            .unwrap();
+        // Earlier computations on the same engine must not change the permutation
+        let mut busy = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        busy.compute("shuffle", &input[..12]).unwrap_err();
+        busy.compute("hash", b"warm-up").unwrap();
+        let second = busy.compute("shuffle", &input).unwrap();
+        assert_eq!(first.value, second.value);
+
+        let shuffled = decode(&first.value);
+        assert_ne!(shuffled, values);
+        let (mut sorted_in, mut sorted_out) = (values.clone(), shuffled);
+        sorted_in.sort_unstable();
+        sorted_out.sort_unstable();
+        assert_eq!(sorted_in, sorted_out);
+
+        let other_seed = DeterministicEngine::new(Some(43), &TEST_SIGNING_KEY)
+            .compute("shuffle", &input)
+            .unwrap();
+        assert_ne!(other_seed.value, first.value);
+    }
+
+    #[test]
+    fn test_commitment_opens_only_to_committed_value() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let result = engine.compute("fibonacci", &[40]).unwrap();
+        let (commitment, opening) = engine.commit_result(result.value_bytes());
+
+        assert!(commitment::verify_commitment(
+            &commitment,
+            result.value_bytes(),
+            &opening
+        ));
+        let wrong = 102_334_156u64.to_be_bytes();
+        assert!(!commitment::verify_commitment(
+            &commitment,
+            &wrong,
+            &opening
+        ));
+
+        // Fresh randomness per commitment keeps equal values unlinkable
+        let (again, _) = engine.commit_result(result.value_bytes());
+        assert_ne!(again, commitment);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_concurrent_compute_async_results_verify() {
+        let engine = Arc::new(Mutex::new(DeterministicEngine::new(
+            Some(42),
+            &TEST_SIGNING_KEY,
+        )));
+        let tasks: Vec<_> = (0..8u8)
+            .map(|n| {
+                let input = vec![n * 10];
+                tokio::spawn(DeterministicEngine::compute_async(
+                    engine.clone(),
+                    "fibonacci".to_string(),
+                    input,
+                ))
+            })
+            .collect();
+
+        for (n, task) in tasks.into_iter().enumerate() {
+            let result = task.await.unwrap().unwrap();
+            let input = [n as u8 * 10];
+            assert!(engine
+                .lock()
+                .unwrap()
+                .verify_result(&result, "fibonacci", &input)
+                .unwrap());
+        }
+    }
+
+    #[test]
+    fn test_seed_sweep_results_verify_under_own_seed() {
+        let mut input = Vec::new();
+        input.extend_from_slice(&3u32.to_be_bytes());
+        input.extend_from_slice(&1.5f64.to_be_bytes());
+        input.extend_from_slice(b"sweep");
+
+        let mut engine = DeterministicEngine::new(Some(1), &TEST_SIGNING_KEY);
+        let seeds = [11, 22, 33];
+        let results = engine
+            .compute_seed_sweep("polynomial_eval", &input, &seeds)
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_ne!(results[0].value, results[1].value);
+        assert_ne!(results[1].value, results[2].value);
+        for (result, seed) in results.iter().zip(seeds) {
+            assert_eq!(result.seed, seed);
+            assert!(DeterministicEngine::new(Some(seed), &TEST_SIGNING_KEY)
+                .verify_result(result, "polynomial_eval", &input)
+                .unwrap());
+        }
+
+        // The engine's own seed is restored afterwards
+        assert_eq!(engine.compute("polynomial_eval", &input).unwrap().seed, 1);
+    }
+
+    #[test]
+    fn test_supported_operations_reflect_allow_list() {
+        let engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        assert_eq!(engine.supported_operations(), BUILTIN_OPERATIONS);
+
+        // Every advertised operation is actually dispatched by `compute`
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        for operation in BUILTIN_OPERATIONS {
+            if let Err(e) = engine.compute(operation, &[]) {
+                assert!(
+                    !e.to_string().starts_with("Unknown operation"),
+                    "{} not dispatched",
+                    operation
+                );
+            }
+        }
+
+        let restricted = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_allowed_operations(&["sort", "hash"]);
+        assert_eq!(restricted.supported_operations(), vec!["hash", "sort"]);
+    }
+
+    #[test]
+    fn test_fibonacci_sequence_satisfies_recurrence() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        for n in [0u8, 1, 2, 50, 93] {
+            let result = engine.compute("fibonacci_sequence", &[n]).unwrap();
+            assert_eq!(result.value.len(), 8 * (n as usize + 1));
+
+            let terms: Vec<u64> = result
+                .value
+                .chunks_exact(8)
+                .map(|c| u64::from_be_bytes(c.try_into().unwrap()))
+                .collect();
+            assert_eq!(terms[0], 0);
+            for k in 2..terms.len() {
+                assert_eq!(terms[k], terms[k - 1] + terms[k - 2]);
+            }
+        }
+
+        let err = engine.compute("fibonacci_sequence", &[94]).unwrap_err();
+        assert_eq!(err.to_string(), "Fibonacci sequence overflows u64");
+        assert_eq!(
+            engine
+                .estimate_cost("fibonacci_sequence", &[93])
+                .unwrap()
+                .output_size,
+            Some(8 * 94)
+        );
+        assert!(engine.estimate_cost("fibonacci_sequence", &[94]).is_err());
+    }
+
+    #[test]
+    fn test_disabled_cache_recomputes_every_call() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        // Only computed (missed) results read the clock, so ticks count misses
+        struct TickClock(Arc<AtomicU64>);
+        impl Clock for TickClock {
+            fn now(&self) -> u64 {
+                self.0.fetch_add(1, Ordering::SeqCst) + 1
+            }
+        }
+
+        let mut uncached = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_clock(TickClock(Arc::new(AtomicU64::new(0))))
+            .with_caching(false);
+        let first = uncached.compute("hash", b"secret").unwrap();
+        let second = uncached.compute("hash", b"secret").unwrap();
+        assert_eq!((first.timestamp, second.timestamp), (1, 2));
+        assert_eq!(first.value, second.value);
+        assert_eq!(uncached.cached_keys().count(), 0);
+        assert_eq!(uncached.cache_bytes(), 0);
+
+        let ticks = Arc::new(AtomicU64::new(0));
+        let mut cached = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_clock(TickClock(ticks.clone()));
+        cached.compute("hash", b"secret").unwrap();
+        assert_eq!(cached.compute("hash", b"secret").unwrap().timestamp, 1);
+        assert_eq!(ticks.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_legacy_verifier_accepts_v1_results() {
+        use deterministic_engine::verifier::attestation::Signature;
+        use legacy::V1_PROOF_DOMAIN_TAG;
+
+        // Rebuild a result exactly as the v1 scheme produced and stored it
+        let seed = 42;
+        let mut engine = DeterministicEngine::new(Some(seed), &TEST_SIGNING_KEY);
+        let value = engine.compute("fibonacci", &[30]).unwrap().value;
+        let proof_hash = legacy::proof_v1(V1_PROOF_DOMAIN_TAG, "fibonacci", &[30], &value, seed);
+        let verification_signature = Signature::from_hex(
+            &DeterministicCrypto::new(seed)
+                .sign_result(&value, &proof_hash)
+                .unwrap(),
+        )
+        .unwrap();
+        let stored = serde_json::to_string(&ComputationResult {
+            value,
+            proof_hash,
+            timestamp: 0,
+            seed,
+            verification_signature,
+            execution_time_ns: 0,
+            cpu_time_ns: None,
+        })
+        .unwrap();
+
+        let v1_result: ComputationResult = serde_json::from_str(&stored).unwrap();
+        assert!(!engine
+            .verify_result(&v1_result, "fibonacci", &[30])
+            .unwrap());
+        assert!(LegacyVerifier::new(seed)
+            .verify(&v1_result, "fibonacci", &[30])
+            .unwrap());
+        assert!(!LegacyVerifier::new(seed)
+            .verify(&v1_result, "fibonacci", &[31])
+            .unwrap());
+
+        // v2 results are not mistaken for v1
+        let v2_result = engine.compute("fibonacci", &[30]).unwrap();
+        assert!(!LegacyVerifier::new(seed)
+            .verify(&v2_result, "fibonacci", &[30])
+            .unwrap());
+    }
+
+    #[test]
+    fn test_base_convert_between_binary_decimal_hex() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let mut convert = |from: u8, to: u8, digits: &str| {
+            let mut input = vec![from, to];
+            input.extend_from_slice(digits.as_bytes());
+            engine
+                .compute("base_convert", &input)
+                .map(|result| String::from_utf8(result.value).unwrap())
+        };
+
+        assert_eq!(convert(10, 16, "255").unwrap(), "ff");
+        assert_eq!(convert(16, 2, "FF").unwrap(), "11111111");
+        assert_eq!(convert(2, 10, "101010").unwrap(), "42");
+        assert_eq!(convert(10, 36, "0").unwrap(), "0");
+        // Values wider than u64 convert exactly
+        assert_eq!(
+            convert(16, 10, "10000000000000000").unwrap(),
+            "18446744073709551616"
+        );
+
+        assert!(convert(10, 37, "1").is_err());
+        assert!(convert(1, 10, "1").is_err());
+        assert!(convert(2, 10, "102").is_err());
+    }
+
+    #[test]
+    fn test_minimal_benchmark_config_reports_configured_cases() {
+        let output_dir = std::env::temp_dir().join(format!("benchmarks-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&output_dir).unwrap();
+        let config = BenchmarkConfig {
+            hash_sizes: vec![64],
+            matrix_sizes: vec![2],
+            fibonacci_ns: vec![5, 6],
+            small_hash_iterations: 10,
+            timing_samples: 2,
+            polynomial_cases: vec![(2, 16)],
+            warmup_iterations: 1,
+            iterations: 3,
+            output_dir: output_dir.clone(),
+        };
+
+        let cases = run_benchmarks(&config, false).unwrap();
+        let labels: Vec<(&str, &str)> = cases
+            .iter()
+            .map(|c| (c.operation.as_str(), c.label.as_str()))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                ("hash", "64 bytes"),
+                ("matrix_multiply", "2x2"),
+                ("fibonacci", "F(5)"),
+                ("fibonacci", "F(6)"),
+                ("polynomial_eval", "degree 2 over 16 bytes"),
+            ]
+        );
+        assert!(output_dir.join("determinism_checks.jsonl").exists());
+        assert!(output_dir.join("deterministic_audit.json").exists());
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_benchmark_case_reports_mean_and_stddev() {
+        let config = BenchmarkConfig {
+            warmup_iterations: 1,
+            iterations: 4,
+            ..BenchmarkConfig::default()
+        };
+        let mut cached = DeterministicEngine::new(Some(1), &TEST_SIGNING_KEY);
+        let mut uncached = DeterministicEngine::new(Some(1), &TEST_SIGNING_KEY).with_caching(false);
+        let case = measure_case(
+            &mut cached,
+            &mut uncached,
+            "hash",
+            "4 KiB".to_string(),
+            &[7u8; 4096],
+            &config,
+        )
+        .unwrap();
+
+        for stats in [case.cache_miss, case.cache_hit] {
+            assert!(stats.mean_ns > 0.0);
+            assert!(stats.stddev_ns >= 0.0);
+        }
+        assert_eq!(uncached.cached_keys().count(), 0);
+        assert_eq!(cached.cached_keys().count(), 1);
+
+        assert_eq!(
+            mean_and_stddev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]),
+            (5.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn test_sample_is_reproducible_and_sized() {
+        let sample_input =
+            |k: u32, values: &[u64]| [&k.to_be_bytes()[..], &encode_u64_list(values)].concat();
+        let decode = |bytes: &[u8]| -> Vec<u64> {
+            assert_eq!(read_u32_be(bytes, 0).unwrap() as usize * 8, bytes.len() - 4);
+            bytes[4..]
+                .chunks_exact(8)
+                .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+                .collect()
+        };
+
+        let values: Vec<u64> = (100..1100).collect();
+        let input = sample_input(10, &values);
+        let first = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .compute("sample", &input)
+            .unwrap();
+        let second = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .compute("sample", &input)
+            .unwrap();
+        assert_eq!(first.value, second.value);
+
+        let sample = decode(&first.value);
+        assert_eq!(sample.len(), 10);
+        assert!(sample.iter().all(|value| values.contains(value)));
+        assert_ne!(sample, values[..10]);
+
+        // With fewer items than k, everything is returned
+        let small = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .compute("sample", &sample_input(10, &[1, 2, 3]))
+            .unwrap();
+        assert_eq!(decode(&small.value), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rolling_hash_boundaries_are_content_defined() {
+        let boundaries = |data: &[u8]| -> Vec<u64> {
+            let mut input = vec![4];
+            input.extend_from_slice(data);
+            let result = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("rolling_hash", &input)
+                .unwrap();
+            assert_eq!(
+                read_u32_be(&result.value, 0).unwrap() as usize * 8,
+                result.value.len() - 4
+            );
+            result.value[4..]
+                .chunks_exact(8)
+                .map(|chunk| read_u64_be(chunk, 0).unwrap())
+                .collect()
+        };
+
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let data: Vec<u8> = (0..4096)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect();
+        let original = boundaries(&data);
+        assert_eq!(original, boundaries(&data));
+        assert!(
+            original.len() > 100,
+            "expected ~256 cuts with a 4-bit mask, got {}",
+            original.len()
+        );
+
+        // Only windows covering the edited byte can move a boundary
+        let edit = 2000;
+        let mut edited = data.clone();
+        edited[edit] ^= 0xFF;
+        let changed = boundaries(&edited);
+        assert_ne!(original, changed);
+        let outside = |cuts: &[u64]| -> Vec<u64> {
+            cuts.iter()
+                .copied()
+                .filter(|&cut| cut <= edit as u64 || cut > (edit + ROLLING_HASH_WINDOW) as u64)
+                .collect()
+        };
+        assert_eq!(outside(&original), outside(&changed));
+    }
+
+    #[test]
+    fn test_set_union_and_intersection() {
+        let operands =
+            |left: &[u64], right: &[u64]| [encode_u64_list(left), encode_u64_list(right)].concat();
+        let run = |operation: &str, left: &[u64], right: &[u64]| -> Vec<u64> {
+            let result = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute(operation, &operands(left, right))
+                .unwrap();
+            assert_eq!(
+                read_u32_be(&result.value, 0).unwrap() as usize * 8,
+                result.value.len() - 4
+            );
+            result.value[4..]
+                .chunks_exact(8)
+                .map(|chunk| read_u64_be(chunk, 0).unwrap())
+                .collect()
+        };
+
+        // Disjoint
+        assert_eq!(run("set_union", &[1, 5, 9], &[2, 3]), vec![1, 2, 3, 5, 9]);
+        assert_eq!(
+            run("set_intersection", &[1, 5, 9], &[2, 3]),
+            Vec::<u64>::new()
+        );
+
+        // Overlapping, with duplicates collapsed
+        assert_eq!(
+            run("set_union", &[1, 2, 2, 4], &[2, 3, 4, 4, 8]),
+            vec![1, 2, 3, 4, 8]
+        );
+        assert_eq!(
+            run("set_intersection", &[1, 2, 2, 4], &[2, 3, 4, 4, 8]),
+            vec![2, 4]
+        );
+
+        // Identical
+        assert_eq!(run("set_union", &[7, 11], &[7, 11]), vec![7, 11]);
+        assert_eq!(run("set_intersection", &[7, 11], &[7, 11]), vec![7, 11]);
+
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        assert!(engine
+            .compute("set_union", &operands(&[3, 1], &[]))
+            .is_err());
+        assert!(engine
+            .compute("set_union", &operands(&[1], &[2])[..15])
+            .is_err());
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let histogram = |buckets: u32, values: &[u64]| -> Vec<u64> {
+            let input = [&buckets.to_be_bytes()[..], &encode_u64_list(values)].concat();
+            let result = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("histogram", &input)
+                .unwrap();
+            assert_eq!(read_u32_be(&result.value, 0).unwrap(), buckets);
+            result.value[4..]
+                .chunks_exact(8)
+                .map(|chunk| read_u64_be(chunk, 0).unwrap())
+                .collect()
+        };
+
+        // Uniform spread: 100 values over 10 buckets
+        let uniform: Vec<u64> = (1000..1100).collect();
+        assert_eq!(histogram(10, &uniform), vec![10; 10]);
+
+        // All-equal values fill only the first bucket
+        assert_eq!(histogram(4, &[5; 7]), vec![7, 0, 0, 0]);
+
+        assert_eq!(histogram(3, &[]), vec![0, 0, 0]);
+        assert_eq!(histogram(2, &[0, u64::MAX]), vec![1, 1]);
+
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        assert!(engine
+            .compute("histogram", &[0, 0, 0, 0, 0, 0, 0, 0])
+            .is_err());
+    }
+
+    #[test]
+    fn test_quantile_uses_linear_interpolation() {
+        let quantile_input =
+            |q: f64, values: &[f64]| [&q.to_be_bytes()[..], &encode_f64_list(values)].concat();
+        let quantile = |q: f64, values: &[f64]| -> f64 {
+            let result = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("quantile", &quantile_input(q, values))
+                .unwrap();
+            read_f64_be(&result.value, 0).unwrap()
+        };
+
+        // Median of an even-length list interpolates between the middle pair
+        assert_eq!(quantile(0.5, &[4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert_eq!(quantile(0.5, &[7.0, 3.0, 5.0]), 5.0);
+
+        // 90th percentile of 1..=10: h = 9 * 0.9 = 8.1, so 9 + 0.1 * (10 - 9)
+        let deciles: Vec<f64> = (1..=10).rev().map(f64::from).collect();
+        assert!((quantile(0.9, &deciles) - 9.1).abs() < 1e-12);
+        assert_eq!(quantile(0.0, &deciles), 1.0);
+        assert_eq!(quantile(1.0, &deciles), 10.0);
+
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        assert!(engine
+            .compute("quantile", &quantile_input(0.5, &[1.0, f64::NAN]))
+            .is_err());
+        assert!(engine
+            .compute("quantile", &quantile_input(0.5, &[f64::INFINITY]))
+            .is_err());
+        assert!(engine
+            .compute("quantile", &quantile_input(1.5, &[1.0]))
+            .is_err());
+        assert!(engine
+            .compute("quantile", &quantile_input(0.5, &[]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_context_scopes_proofs_and_cache_entries() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let tenant_a = engine
+            .compute_with_context("fibonacci", &[20], b"tenant-a")
+            .unwrap();
+        let tenant_b = engine
+            .compute_with_context("fibonacci", &[20], b"tenant-b")
+            .unwrap();
+
+        assert_eq!(tenant_a.value, tenant_b.value);
+        assert_ne!(tenant_a.proof_hash, tenant_b.proof_hash);
+        assert_eq!(engine.cached_keys().count(), 2);
+
+        assert!(engine
+            .verify_result_with_context(&tenant_a, "fibonacci", &[20], b"tenant-a")
+            .unwrap());
+        assert!(!engine
+            .verify_result_with_context(&tenant_a, "fibonacci", &[20], b"tenant-b")
+            .unwrap());
+        assert!(!engine.verify_result(&tenant_a, "fibonacci", &[20]).unwrap());
+
+        // An empty context is plain `compute`, sharing its cache entry and proof
+        let plain = engine.compute("fibonacci", &[20]).unwrap();
+        let empty = engine
+            .compute_with_context("fibonacci", &[20], b"")
+            .unwrap();
+        assert_eq!(plain, empty);
+        assert_eq!(engine.cached_keys().count(), 3);
+    }
+
+    #[test]
+    fn test_cache_keys_separate_operation_from_input() {
+        // Both calls hash to "fibonacci_sequence\x05" if the fields are not framed
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let sequence = engine.compute("fibonacci_sequence", &[5]).unwrap();
+        assert_eq!(sequence.value.len(), 8 * 6);
+
+        let err = engine.compute("fibonacci", b"_sequence\x05").unwrap_err();
+        assert_eq!(err.to_string(), "Fibonacci number too large");
+        assert_ne!(
+            engine.generate_computation_key("fibonacci_sequence", &[5], &[]),
+            engine.generate_computation_key("fibonacci", b"_sequence\x05", &[])
+        );
+    }
+
+    #[test]
+    fn test_sign_over_digest_is_bound_to_the_digest_path() {
+        let input = vec![0xA5u8; 1 << 20];
+        let digest: [u8; 32] = Sha256::digest(&input).into();
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+
+        let over_digest = engine
+            .compute_over_digest("sign", &digest, input.len() as u64)
+            .unwrap();
+        let full_digest = engine.compute("sign", &digest).unwrap();
+
+        // Same signature over the digest bytes, but a proof marking the pre-digest path
+        assert_eq!(over_digest.value, full_digest.value);
+        assert_ne!(over_digest.proof_hash, full_digest.proof_hash);
+        assert!(engine
+            .verify_digest_result(&over_digest, "sign", &digest, input.len() as u64)
+            .unwrap());
+        assert!(!engine
+            .verify_digest_result(&over_digest, "sign", &digest, 1)
+            .unwrap());
+        assert!(!engine.verify_result(&over_digest, "sign", &digest).unwrap());
+        assert!(!engine
+            .verify_digest_result(&full_digest, "sign", &digest, input.len() as u64)
+            .unwrap());
+
+        assert!(engine.compute_over_digest("fibonacci", &digest, 0).is_err());
+    }
+
+    #[test]
+    fn test_cache_hit_emits_tracing_event() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+        /// Records every event's fields as strings
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<BTreeMap<String, String>>>>);
+
+        struct Fields(BTreeMap<String, String>);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .insert(field.name().to_string(), format!("{:?}", value));
+            }
+
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.insert(field.name().to_string(), value.to_string());
+            }
+        }
+
+        impl<S: tracing::Subscriber> Layer<S> for Capture {
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                let mut fields = Fields(BTreeMap::new());
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+            }
+        }
+
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        tracing::subscriber::with_default(subscriber, || {
+            engine.compute("fibonacci", &[30]).unwrap();
+            engine.compute("fibonacci", &[30]).unwrap();
+        });
+
+        let events = capture.0.lock().unwrap();
+        let messages: Vec<&str> = events
+            .iter()
+            .map(|fields| fields["message"].as_str())
+            .collect();
+        assert_eq!(messages, ["cache miss", "cache hit"]);
+        let hit = &events[1];
+        assert_eq!(hit["operation"], "fibonacci");
+        assert_eq!(
+            hit["key"],
+            engine.generate_computation_key("fibonacci", &[30], &[])
+        );
+    }
+
+    #[test]
+    fn test_normalize_utf8_composes_equivalent_names() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let precomposed = engine
+            .compute("normalize_utf8", "caf\u{e9}-r\u{e9}sum\u{e9}".as_bytes())
+            .unwrap();
+        let decomposed = engine
+            .compute(
+                "normalize_utf8",
+                "cafe\u{301}-re\u{301}sume\u{301}".as_bytes(),
+            )
+            .unwrap();
+
+        assert_eq!(precomposed.value, decomposed.value);
+        assert_eq!(precomposed.value, "caf\u{e9}-r\u{e9}sum\u{e9}".as_bytes());
+
+        // Combining marks are reordered canonically before composing
+        let marks_a = engine
+            .compute("normalize_utf8", "a\u{323}\u{302}".as_bytes())
+            .unwrap();
+        let marks_b = engine
+            .compute("normalize_utf8", "a\u{302}\u{323}".as_bytes())
+            .unwrap();
+        assert_eq!(marks_a.value, marks_b.value);
+        assert_eq!(marks_a.value, "\u{1ead}".as_bytes());
+
+        let err = engine
+            .compute("normalize_utf8", b"left\xffpad")
+            .unwrap_err();
+        assert!(
+            err.to_string().starts_with("Invalid UTF-8 input"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_assert_output_attests_expected_value() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let result = engine
+            .assert_output("fibonacci", &[10], &55u64.to_be_bytes())
+            .unwrap();
+        assert_eq!(result.as_u64(), Some(55));
+        assert!(engine.verify_result(&result, "fibonacci", &[10]).unwrap());
+
+        let err = engine
+            .assert_output("fibonacci", &[10], &56u64.to_be_bytes())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ComputeError::OutputMismatch {
+                operation: "fibonacci".to_string(),
+                expected: "0000000000000038".to_string(),
+                actual: "0000000000000037".to_string(),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Output mismatch for fibonacci: expected 0000000000000038, got 0000000000000037"
+        );
+
+        assert!(matches!(
+            engine.assert_output("fibonacci", &[], &[]),
+            Err(ComputeError::Failed(_))
+        ));
+    }
+
+    #[test]
+    fn test_crc_operations_match_reference_vectors() {
+        // Standard check values pin the CRC parameters
+        assert_eq!(CRC32.checksum(b"123456789"), 0xCBF4_3926);
+        assert_eq!(CRC64.checksum(b"123456789"), 0x995D_C9BB_DF19_39FA);
+
+        // The engine prefixes the seed: for seed 42 this is zlib.crc32(b"\0\0\0\0\0\0\0\x2a123456789")
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let crc32 = engine.compute("crc32", b"123456789").unwrap();
+        assert_eq!(crc32.value, 0xBC6D_4F2Cu32.to_be_bytes());
+
+        let mut seeded = 42u64.to_be_bytes().to_vec();
+        seeded.extend_from_slice(b"123456789");
+        let crc64 = engine.compute("crc64", b"123456789").unwrap();
+        assert_eq!(crc64.value, CRC64.checksum(&seeded).to_be_bytes());
+
+        let other_seed = DeterministicEngine::new(Some(43), &TEST_SIGNING_KEY)
+            .compute("crc32", b"123456789")
+            .unwrap();
+        assert_ne!(other_seed.value, crc32.value);
+    }
+
+    #[test]
+    fn test_top_k_returns_largest_values_descending() {
+        let top_k = |k: u32, values: &[u64]| -> Vec<u64> {
+            let input = [&k.to_be_bytes()[..], &encode_u64_list(values)].concat();
+            let bytes = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("top_k", &input)
+                .unwrap()
+                .value;
+            assert_eq!(
+                read_u32_be(&bytes, 0).unwrap() as usize * 8,
+                bytes.len() - 4
+            );
+            bytes[4..]
+                .chunks_exact(8)
+                .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+                .collect()
+        };
+
+        assert_eq!(top_k(3, &[4, 17, 1, 9, 12, 3]), vec![17, 12, 9]);
+
+        // Ties at the cut-off keep the earliest occurrences
+        assert_eq!(top_k(3, &[5, 9, 5, 7, 5]), vec![9, 7, 5]);
+        assert_eq!(top_k(2, &[8, 8, 8]), vec![8, 8]);
+
+        // k beyond the list returns everything, sorted
+        assert_eq!(top_k(10, &[2, 6, 4]), vec![6, 4, 2]);
+        assert_eq!(top_k(0, &[2, 6, 4]), Vec::<u64>::new());
+
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        assert!(engine.compute("top_k", &[0, 0, 0, 1, 0, 0, 0, 2]).is_err());
+    }
+
+    #[test]
+    fn test_config_fingerprint_tracks_result_affecting_settings() {
+        use deterministic_engine::hasher::Blake3Hasher;
+
+        let base = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY).config_fingerprint();
+        assert_eq!(base.len(), 64);
+        assert_eq!(
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY).config_fingerprint(),
+            base
+        );
+
+        // Settings that leave results unchanged leave the fingerprint unchanged
+        let tuned = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_caching(false)
+            .with_cpu_time_measurement(true)
+            .with_allowed_operations(&["hash"])
+            .with_hasher(Sha256Hasher::default())
+            .with_domain_tag(DEFAULT_PROOF_DOMAIN_TAG);
+        assert_eq!(tuned.config_fingerprint(), base);
+
+        let changed = [
+            DeterministicEngine::new(Some(43), &TEST_SIGNING_KEY).config_fingerprint(),
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .with_hasher(Blake3Hasher::default())
+                .config_fingerprint(),
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .with_domain_tag("deployment-a")
+                .config_fingerprint(),
+            // Another signing key changes every signature and the verifying key
+            DeterministicEngine::new(Some(42), &[8; 32]).config_fingerprint(),
+        ];
+        for fingerprint in &changed {
+            assert_ne!(fingerprint, &base);
+        }
+        assert_ne!(changed[0], changed[1]);
+        assert_ne!(changed[1], changed[2]);
+        assert_ne!(changed[2], changed[3]);
+    }
+
+    #[test]
+    fn test_base64_and_base32_round_trip() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+
+        // RFC 4648 test vectors
+        assert_eq!(
+            engine.compute("base64_encode", b"foobar").unwrap().value,
+            b"Zm9vYmFy"
+        );
+        assert_eq!(
+            engine.compute("base64_encode", b"fo").unwrap().value,
+            b"Zm8="
+        );
+        assert_eq!(
+            engine.compute("base32_encode", b"foobar").unwrap().value,
+            b"MZXW6YTBOI======"
+        );
+
+        let data: Vec<u8> = (0..=255).collect();
+        for (encode, decode) in [
+            ("base64_encode", "base64_decode"),
+            ("base32_encode", "base32_decode"),
+        ] {
+            for len in [0, 1, 2, 3, 4, 5, 256] {
+                let text = engine.compute(encode, &data[..len]).unwrap().value;
+                assert_eq!(engine.compute(decode, &text).unwrap().value, &data[..len]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_base64_is_a_descriptive_error() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+
+        for text in [&b"Zm9v!mFy"[..], b"Zm9vYmF", b"Zm8", b"Zm9=", b"Zm9v YmFy"] {
+            let error = engine
+                .compute("base64_decode", text)
+                .unwrap_err()
+                .to_string();
+            assert!(error.starts_with("Invalid Base64 input: "), "{}", error);
+        }
+
+        let error = engine
+            .compute("base32_decode", b"MZXW6YT1")
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Invalid Base32 input: "), "{}", error);
+    }
+
+    #[test]
+    fn test_matrix_transpose_handles_empty_dimensions() {
+        let matrix_input = |rows: u32, cols: u32, values: &[f64]| {
+            let mut input = Vec::new();
+            input.extend_from_slice(&rows.to_be_bytes());
+            input.extend_from_slice(&cols.to_be_bytes());
+            for value in values {
+                input.extend_from_slice(&value.to_be_bytes());
+            }
+            input
+        };
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+
+        // Zero rows or zero columns give the swapped header and no cells
+        let no_rows = engine
+            .compute("matrix_multiply", &matrix_input(0, 3, &[]))
+            .unwrap();
+        assert_eq!(no_rows.value, matrix_input(3, 0, &[]));
+        let no_cols = engine
+            .compute("matrix_multiply", &matrix_input(4, 0, &[]))
+            .unwrap();
+        assert_eq!(no_cols.value, matrix_input(0, 4, &[]));
+
+        let normal = engine
+            .compute(
+                "matrix_multiply",
+                &matrix_input(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+            )
+            .unwrap();
+        assert_eq!(
+            normal.value,
+            matrix_input(3, 2, &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0])
+        );
+    }
+
+    #[test]
+    fn test_freshness_check_rejects_old_results() {
+        let mut producer = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_clock(FixedClock(1_700_000_000));
+        let result = producer.compute("hash", b"fresh").unwrap();
+
+        let hour = Duration::from_secs(3600);
+        let later = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_clock(FixedClock(1_700_000_000 + 2 * 3600));
+        assert!(later.verify_result(&result, "hash", b"fresh").unwrap());
+        assert!(!later
+            .verify_result_fresh(&result, "hash", b"fresh", hour)
+            .unwrap());
+        assert!(later
+            .verify_result_fresh(&result, "hash", b"fresh", 3 * hour)
+            .unwrap());
+
+        // Timestamps from the future are bounded by the same window
+        let earlier = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_clock(FixedClock(1_700_000_000 - 2 * 3600));
+        assert!(!earlier
+            .verify_result_fresh(&result, "hash", b"fresh", hour)
+            .unwrap());
+
+        // Freshness never rescues an invalid result
+        assert!(!producer
+            .verify_result_fresh(&result, "hash", b"stale", hour)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_lu_factors_reconstruct_permuted_matrix() {
+        let matrix = [2.0f64, 1.0, 1.0, 4.0, -6.0, 0.0, -2.0, 7.0, 2.0];
+        let mut input = Vec::new();
+        input.extend_from_slice(&3u32.to_be_bytes());
+        input.extend_from_slice(&3u32.to_be_bytes());
+        for value in matrix {
+            input.extend_from_slice(&value.to_be_bytes());
+        }
+
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let output = engine.compute("matrix_lu", &input).unwrap().value;
+        assert_eq!(read_u32_be(&output, 0).unwrap(), 3);
+        let perm: Vec<usize> = (0..3)
+            .map(|i| read_u32_be(&output, 4 + 4 * i).unwrap() as usize)
+            .collect();
+        let cells: Vec<f64> = output[16..]
+            .chunks_exact(8)
+            .map(|chunk| read_f64_be(chunk, 0).unwrap())
+            .collect();
+        let (lower, upper) = cells.split_at(9);
+
+        // Column 0 pivots on 4.0 (row 1); column 1 then ties at 4.0 and keeps the earlier row
+        assert_eq!(perm, vec![1, 0, 2]);
+        for i in 0..3 {
+            assert_eq!(lower[i * 3 + i], 1.0);
+            for j in 0..3 {
+                if j > i {
+                    assert_eq!(lower[i * 3 + j], 0.0);
+                }
+                if j < i {
+                    assert_eq!(upper[i * 3 + j], 0.0);
+                }
+                let product: f64 = (0..3).map(|k| lower[i * 3 + k] * upper[k * 3 + j]).sum();
+                assert!((product - matrix[perm[i] * 3 + j]).abs() < 1e-12);
+            }
+        }
+
+        let mut singular = Vec::new();
+        singular.extend_from_slice(&2u32.to_be_bytes());
+        singular.extend_from_slice(&2u32.to_be_bytes());
+        for value in [1.0f64, 2.0, 2.0, 4.0] {
+            singular.extend_from_slice(&value.to_be_bytes());
+        }
+        let error = engine
+            .compute("matrix_lu", &singular)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("singular"), "{}", error);
+    }
+
+    #[test]
+    fn test_rle_round_trips_runs_and_worst_case_input() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+
+        assert_eq!(
+            engine.compute("rle_encode", b"aaabcc").unwrap().value,
+            b"\x03a\x01b\x02c"
+        );
+
+        let long_run = vec![7u8; 600];
+        assert_eq!(
+            engine.compute("rle_encode", &long_run).unwrap().value,
+            [255, 7, 255, 7, 90, 7]
+        );
+
+        // No runs at all: every byte becomes its own pair
+        let distinct: Vec<u8> = (0..=255).collect();
+        let mixed: Vec<u8> = b"xx"
+            .iter()
+            .chain(&long_run)
+            .chain(&distinct)
+            .copied()
+            .collect();
+        for data in [&b""[..], &distinct, &long_run, &mixed] {
+            let encoded = engine.compute("rle_encode", data).unwrap().value;
+            assert_eq!(engine.compute("rle_decode", &encoded).unwrap().value, data);
+        }
+        assert_eq!(
+            engine.compute("rle_encode", &distinct).unwrap().value.len(),
+            512
+        );
+    }
+
+    #[test]
+    fn test_rle_decode_rejects_malformed_streams() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+
+        for (stream, expected) in [
+            (&b"\x02a\x01"[..], "whole (count, byte) pairs"),
+            (b"\x02a\x00b", "zero count"),
+            (b"\x02a\x01a", "should have been merged"),
+        ] {
+            let error = engine
+                .compute("rle_decode", stream)
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains(expected), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_merge_sorted_keeps_duplicates() {
+        let merge = |left: &[u64], right: &[u64]| -> Vec<u64> {
+            let input = [encode_u64_list(left), encode_u64_list(right)].concat();
+            let result = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("merge_sorted", &input)
+                .unwrap();
+            assert_eq!(
+                read_u32_be(&result.value, 0).unwrap() as usize,
+                left.len() + right.len()
+            );
+            result.value[4..]
+                .chunks_exact(8)
+                .map(|chunk| read_u64_be(chunk, 0).unwrap())
+                .collect()
+        };
+
+        // Interleaved
+        assert_eq!(merge(&[1, 4, 6, 9], &[2, 3, 7]), vec![1, 2, 3, 4, 6, 7, 9]);
+
+        // Fully disjoint, in either order
+        assert_eq!(merge(&[10, 11], &[1, 2, 3]), vec![1, 2, 3, 10, 11]);
+        assert_eq!(merge(&[1, 2, 3], &[10, 11]), vec![1, 2, 3, 10, 11]);
+        assert_eq!(merge(&[], &[5, 5]), vec![5, 5]);
+
+        // Duplicate-heavy: nothing is collapsed
+        assert_eq!(
+            merge(&[2, 2, 2, 5], &[2, 2, 5, 5, 5]),
+            vec![2, 2, 2, 2, 2, 5, 5, 5, 5]
+        );
+
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let unsorted = [
+            &2u32.to_be_bytes()[..],
+            &9u64.to_be_bytes(),
+            &1u64.to_be_bytes(),
+            &0u32.to_be_bytes(),
+        ]
+        .concat();
+        assert!(engine.compute("merge_sorted", &unsorted).is_err());
+    }
+
+    #[test]
+    fn test_truncated_hash_is_verified_prefix_of_full_digest() {
+        let mut full = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let mut truncated = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_hash_truncation(16)
+            .unwrap();
+        let digest = full.compute("hash", b"truncate me").unwrap();
+        let short = truncated.compute("hash", b"truncate me").unwrap();
+
+        assert_eq!(digest.value.len(), 32);
+        assert_eq!(short.value, digest.value[..16]);
+        assert!(truncated
+            .verify_result(&short, "hash", b"truncate me")
+            .unwrap());
+        assert_eq!(
+            truncated
+                .estimate_cost("hash", b"truncate me")
+                .unwrap()
+                .output_size,
+            Some(16)
+        );
+
+        // The proof records the length, so neither result passes for the other
+        assert_ne!(short.proof_hash, digest.proof_hash);
+        assert!(!full.verify_result(&short, "hash", b"truncate me").unwrap());
+        assert!(!truncated
+            .verify_result(&digest, "hash", b"truncate me")
+            .unwrap());
+        let params = truncated.export_verification_params();
+        assert!(deterministic_engine::verifier::engine::verify_with_params(
+            &short,
+            "hash",
+            b"truncate me",
+            &params
+        )
+        .unwrap());
+
+        assert!(matches!(
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY).with_hash_truncation(8),
+            Err(ComputeError::InvalidTruncation(_))
+        ));
+        assert!(DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_hash_truncation(33)
+            .is_err());
+    }
+
+    #[test]
+    fn test_dot_product_accumulates_left_to_right() {
+        let dot = |a: &[f64], b: &[f64]| {
+            let input = [encode_f64_list(a), encode_f64_list(b)].concat();
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("dot_product", &input)
+                .map(|result| read_f64_be(&result.value, 0).unwrap())
+        };
+
+        assert_eq!(dot(&[1.0, 2.0, 3.0], &[4.0, -5.0, 6.0]).unwrap(), 12.0);
+        assert_eq!(dot(&[], &[]).unwrap(), 0.0);
+
+        // ((0 + 1e16) + 1) rounds back to 1e16, so the exact answer of 1 is lost;
+        // cancelling the large products first keeps it
+        assert_eq!(dot(&[1e16, 1.0, -1e16], &[1.0, 1.0, 1.0]).unwrap(), 0.0);
+        assert_eq!(dot(&[1e16, -1e16, 1.0], &[1.0, 1.0, 1.0]).unwrap(), 1.0);
+
+        let error = dot(&[1.0, 2.0], &[3.0]).unwrap_err().to_string();
+        assert!(error.contains("differ in length"), "{}", error);
+        assert!(dot(&[f64::NAN], &[1.0]).is_err());
+        assert!(dot(&[1.0], &[f64::INFINITY]).is_err());
+    }
+
+    #[test]
+    fn test_cache_verification_detects_corrupted_entry() {
+        let mut engine =
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY).with_cache_verification(true);
+        let original = engine.compute("fibonacci", &[50]).unwrap();
+        assert_eq!(engine.compute("fibonacci", &[50]).unwrap(), original);
+
+        let key = engine.cached_keys().next().unwrap().to_string();
+        engine.computation_cache.get_mut(&key).unwrap().result.value[7] ^= 1;
+
+        let error = engine.compute("fibonacci", &[50]).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ComputeError>(),
+            Some(ComputeError::CacheDivergence { .. })
+        ));
+
+        // The bad entry is gone, so the next call recomputes the true value
+        assert!(engine.get_cached(&key).is_none());
+        assert_eq!(
+            engine.compute("fibonacci", &[50]).unwrap().value,
+            original.value
+        );
+
+        // Without verification the corrupted value is served as is
+        let mut unchecked = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        unchecked.compute("fibonacci", &[50]).unwrap();
+        unchecked
+            .computation_cache
+            .get_mut(&key)
+            .unwrap()
+            .result
+            .value[7] ^= 1;
+        assert_ne!(
+            unchecked.compute("fibonacci", &[50]).unwrap().value,
+            original.value
+        );
+
+        // Crypto operations are not recomputed on a hit, so their entries go unchecked
+        let mut stateful =
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY).with_cache_verification(true);
+        let encrypted = stateful.compute("encrypt", b"plaintext").unwrap();
+        let key = stateful.cached_keys().next().unwrap().to_string();
+        stateful
+            .computation_cache
+            .get_mut(&key)
+            .unwrap()
+            .result
+            .value[0] ^= 1;
+        assert_ne!(
+            stateful.compute("encrypt", b"plaintext").unwrap().value,
+            encrypted.value
+        );
+    }
+
+    #[test]
+    fn test_factorize_returns_prime_exponent_pairs() {
+        let factorize = |n: u64| {
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("factorize", &n.to_be_bytes())
+                .map(|result| {
+                    result
+                        .value
+                        .chunks_exact(16)
+                        .map(|pair| (read_u64_be(pair, 0).unwrap(), read_u64_be(pair, 8).unwrap()))
+                        .collect::<Vec<_>>()
+                })
+        };
+
+        assert_eq!(factorize(1_000_003).unwrap(), vec![(1_000_003, 1)]);
+        assert_eq!(factorize(3u64.pow(13)).unwrap(), vec![(3, 13)]);
+        assert_eq!(factorize(1 << 40).unwrap(), vec![(2, 40)]);
+        assert_eq!(
+            factorize(2 * 2 * 3 * 7 * 7 * 1_000_003).unwrap(),
+            vec![(2, 2), (3, 1), (7, 2), (1_000_003, 1)]
+        );
+        assert!(factorize(1).unwrap().is_empty());
+
+        assert!(factorize(0).is_err());
+        let error = factorize((1 << 40) + 1).unwrap_err().to_string();
+        assert!(error.contains("exceeds the limit"), "{}", error);
+    }
+
+    #[test]
+    fn test_normalize_vector_has_unit_norm() {
+        let normalize = |values: &[f64]| {
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("normalize_vector", &encode_f64_list(values))
+                .map(|result| {
+                    assert_eq!(
+                        read_u32_be(&result.value, 0).unwrap() as usize,
+                        values.len()
+                    );
+                    result.value[4..]
+                        .chunks_exact(8)
+                        .map(|chunk| read_f64_be(chunk, 0).unwrap())
+                        .collect::<Vec<_>>()
+                })
+        };
+
+        assert_eq!(normalize(&[3.0, -4.0]).unwrap(), vec![0.6, -0.8]);
+
+        let unit = normalize(&[1.5, -2.0, 0.25, 7.0, 1e-3]).unwrap();
+        let norm = unit.iter().map(|value| value * value).sum::<f64>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-12, "norm {}", norm);
+
+        // Squares of these overflow f64 without the rescaling step
+        assert_eq!(normalize(&[3e200, 4e200]).unwrap(), vec![0.6, 0.8]);
+
+        let error = normalize(&[0.0, -0.0, 0.0]).unwrap_err().to_string();
+        assert!(error.contains("zero vector"), "{}", error);
+        assert!(normalize(&[]).is_err());
+        assert!(normalize(&[1.0, f64::NAN]).is_err());
+    }
+
+    #[test]
+    fn test_stratified_sample_draws_from_each_quartile() {
+        let stratified = |seed: u64, k: u32, values: &[u64]| {
+            let input = [&k.to_be_bytes()[..], &encode_u64_list(values)].concat();
+            let output = DeterministicEngine::new(Some(seed), &TEST_SIGNING_KEY)
+                .compute("stratified_sample", &input)
+                .unwrap()
+                .value;
+
+            let mut strata = Vec::new();
+            let mut rest = &output[..];
+            for _ in 0..4 {
+                let count = read_u32_be(rest, 0).unwrap() as usize;
+                strata.push(
+                    rest[4..4 + count * 8]
+                        .chunks_exact(8)
+                        .map(|chunk| read_u64_be(chunk, 0).unwrap())
+                        .collect::<Vec<_>>(),
+                );
+                rest = &rest[4 + count * 8..];
+            }
+            assert!(rest.is_empty());
+            strata
+        };
+
+        // 0..40 in a scrambled order: quartiles are 0..10, 10..20, 20..30, 30..40
+        let values: Vec<u64> = (0..40).map(|i| (i * 17) % 40).collect();
+        let strata = stratified(42, 3, &values);
+        for (stratum, sample) in strata.iter().enumerate() {
+            assert_eq!(sample.len(), 3);
+            let range = stratum as u64 * 10..(stratum as u64 + 1) * 10;
+            assert!(
+                sample.iter().all(|value| range.contains(value)),
+                "stratum {}: {:?}",
+                stratum,
+                sample
+            );
+        }
+        assert_eq!(stratified(42, 3, &values), strata);
+        assert_ne!(stratified(43, 3, &values), strata);
+
+        // Strata smaller than k are kept whole; six values split 1, 2, 1, 2
+        let counts: Vec<usize> = stratified(42, 5, &[6, 5, 4, 3, 2, 1])
+            .iter()
+            .map(Vec::len)
+            .collect();
+        assert_eq!(counts, vec![1, 2, 1, 2]);
+    }
+
+    #[test]
+    fn test_moving_average_covers_full_windows_only() {
+        let moving_average = |window: u32, series: &[f64]| {
+            let input = [&window.to_be_bytes()[..], &encode_f64_list(series)].concat();
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("moving_average", &input)
+                .map(|result| {
+                    let count = read_u32_be(&result.value, 0).unwrap() as usize;
+                    assert_eq!(result.value.len(), 4 + count * 8);
+                    result.value[4..]
+                        .chunks_exact(8)
+                        .map(|chunk| read_f64_be(chunk, 0).unwrap())
+                        .collect::<Vec<_>>()
+                })
+        };
+
+        let series = [1.0, 2.0, 6.0, 3.0, -4.0, 10.0];
+        assert_eq!(
+            moving_average(3, &series).unwrap(),
+            vec![3.0, 11.0 / 3.0, 5.0 / 3.0, 3.0]
+        );
+        assert_eq!(moving_average(1, &series).unwrap(), series.to_vec());
+        assert_eq!(moving_average(6, &series).unwrap(), vec![3.0]);
+
+        let error = moving_average(7, &series).unwrap_err().to_string();
+        assert!(error.contains("window 7 outside"), "{}", error);
+        assert!(moving_average(0, &series).is_err());
+        assert!(moving_average(2, &[1.0, f64::INFINITY]).is_err());
+    }
+
+    #[test]
+    fn test_byte_patch_reverses_byte_diff() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let mut diff = |old: &[u8], new: &[u8]| {
+            let mut input = Vec::new();
+            for string in [old, new] {
+                input.extend_from_slice(&(string.len() as u32).to_be_bytes());
+                input.extend_from_slice(string);
+            }
+            engine.compute("byte_diff", &input).unwrap().value
+        };
+        let patch = |old: &[u8], ops: &[u8]| {
+            let mut input = (old.len() as u32).to_be_bytes().to_vec();
+            input.extend_from_slice(old);
+            input.extend_from_slice(ops);
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("byte_patch", &input)
+                .map(|result| result.value)
+        };
+
+        let cases: [(&[u8], &[u8]); 8] = [
+            (b"hello world", b"hello world"),
+            (b"hello world", b"hello brave world"),
+            (b"hello brave world", b"hello world"),
+            (b"the quick brown fox", b"the quack brown box!"),
+            (b"abcdefgh", b"hgfedcba"),
+            (b"", b"fresh content"),
+            (b"all of this goes", b""),
+            (b"aaaa", b"aaaaaaaa"),
+        ];
+        for (old, new) in cases {
+            let ops = diff(old, new);
+            assert_eq!(patch(old, &ops).unwrap(), new, "{:?} -> {:?}", old, new);
+            assert_eq!(diff(old, new), ops);
+        }
+
+        // One insertion between a copied prefix and suffix
+        let mut expected = vec![DIFF_COPY, 0, 0, 0, 6, DIFF_INSERT, 0, 0, 0, 6];
+        expected.extend_from_slice(b"brave ");
+        expected.extend_from_slice(&[DIFF_COPY, 0, 0, 0, 5]);
+        assert_eq!(diff(b"hello world", b"hello brave world"), expected);
+        // A replacement deletes before it inserts
+        assert_eq!(
+            diff(b"cat", b"cot"),
+            vec![
+                DIFF_COPY,
+                0,
+                0,
+                0,
+                1,
+                DIFF_DELETE,
+                0,
+                0,
+                0,
+                1,
+                DIFF_INSERT,
+                0,
+                0,
+                0,
+                1,
+                b'o',
+                DIFF_COPY,
+                0,
+                0,
+                0,
+                1
+            ]
+        );
+        assert_eq!(diff(b"same", b"same"), vec![DIFF_COPY, 0, 0, 0, 4]);
+
+        // A script only applies to a string of the length it was made for
+        let ops = diff(b"hello world", b"hello brave world");
+        assert!(patch(b"hello world!", &ops).is_err());
+        assert!(patch(b"hello", &ops).is_err());
+        assert!(patch(b"hello world", &[9, 0, 0, 0, 1]).is_err());
+
+        // Maximal length prefixes are rejected rather than overflowing
+        assert!(patch(b"hello", &[DIFF_COPY, 0xFF, 0xFF, 0xFF, 0xFF]).is_err());
+        assert!(engine
+            .compute("byte_diff", &[0xFF, 0xFF, 0xFF, 0xFF, b'a'])
+            .is_err());
+    }
+
+    #[test]
+    fn test_audit_report_streams_to_writer() {
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+            .with_clock(FixedClock(1_700_000_000));
+        engine.compute("hash", b"first").unwrap();
+        engine.compute("fibonacci", &[20]).unwrap();
+
+        let mut streamed = Vec::new();
+        engine.write_audit_report(&mut streamed).unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&streamed).unwrap();
+        let body = &report["deterministic_engine"];
+        assert_eq!(body["seed"], 42);
+        assert_eq!(body["total_computations"], 2);
+        assert_eq!(body["timestamp"], 1_700_000_000u64);
+        assert!(body["operation_timings_ns"]["fibonacci"].is_u64());
+
+        // The string export is the same stream, buffered
+        assert_eq!(engine.export_audit_report().into_bytes(), streamed);
+    }
+
+    #[test]
+    fn test_kmeans_assign_picks_nearest_with_low_index_ties() {
+        let assign = |dims: u32, centroids: &[f64], points: &[f64]| {
+            let mut input = Vec::new();
+            for header in [
+                dims,
+                centroids.len() as u32 / dims,
+                points.len() as u32 / dims,
+            ] {
+                input.extend_from_slice(&header.to_be_bytes());
+            }
+            input.extend(
+                centroids
+                    .iter()
+                    .chain(points)
+                    .flat_map(|value| value.to_be_bytes()),
+            );
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY)
+                .compute("kmeans_assign", &input)
+                .map(|result| {
+                    assert_eq!(
+                        read_u32_be(&result.value, 0).unwrap() as usize * 4 + 4,
+                        result.value.len()
+                    );
+                    result.value[4..]
+                        .chunks_exact(4)
+                        .map(|chunk| read_u32_be(chunk, 0).unwrap())
+                        .collect::<Vec<_>>()
+                })
+        };
+
+        let centroids = [0.0, 0.0, 10.0, 0.0, 0.0, 10.0];
+        let points = [
+            1.0, 1.0, // nearest (0, 0)
+            9.0, -1.0, // nearest (10, 0)
+            2.0, 8.5, // nearest (0, 10)
+            5.0, 0.0, // equidistant from (0, 0) and (10, 0): lower index wins
+            5.0, 5.0, // equidistant from all three
+            10.0, 10.0, // equidistant from (10, 0) and (0, 10)
+        ];
+        assert_eq!(
+            assign(2, &centroids, &points).unwrap(),
+            vec![0, 1, 2, 0, 0, 1]
+        );
+        assert_eq!(assign(2, &centroids, &[]).unwrap(), Vec::<u32>::new());
+
+        assert!(assign(2, &[], &points).is_err());
+        assert!(assign(2, &centroids, &[1.0, f64::NAN]).is_err());
+        assert!(assign(2, &[f64::INFINITY, 0.0], &[1.0, 1.0]).is_err());
+    }
+
+    #[test]
+    fn test_result_verifies_under_published_key_alone() {
+        let mut engine_a = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let result = engine_a.compute("hash", b"federated").unwrap();
+        let published = hex::decode(engine_a.export_verification_params().public_key).unwrap();
+
+        // B shares neither seed nor secret with A
+        let engine_b = DeterministicEngine::new(Some(99), &[9; 32]);
+        assert!(!engine_b
+            .verify_result(&result, "hash", b"federated")
+            .unwrap());
+        assert!(engine_b
+            .verify_result_with_key(&result, "hash", b"federated", &published)
+            .unwrap());
+        assert!(!engine_b
+            .verify_result_with_key(&result, "hash", b"tampered", &published)
+            .unwrap());
+
+        let own_key = hex::decode(engine_b.export_verification_params().public_key).unwrap();
+        assert!(!engine_b
+            .verify_result_with_key(&result, "hash", b"federated", &own_key)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_exported_params_alone_verify_results() {
+        use deterministic_engine::verifier::engine::verify_with_params;
+
+        let mut engine =
+            DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY).with_domain_tag("deployment-a");
+        let result = engine.compute("sort", &[0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+
+        // Round-trip through JSON, as a third party would receive the bundle
+        let json = serde_json::to_string(&engine.export_verification_params()).unwrap();
+        let params: VerificationParams = serde_json::from_str(&json).unwrap();
+        assert_eq!(params.domain_tag, hex::encode("deployment-a"));
+        assert_eq!(params.hash_algorithm, "sha256");
+        assert_eq!(
+            params.public_key,
+            hex::encode(RootSigningKey::from_seed(&TEST_SIGNING_KEY).public_key())
+        );
+        assert!(!json.contains("seed"));
+
+        assert!(verify_with_params(&result, "sort", &[0; 8], &params).unwrap());
+        assert!(!verify_with_params(&result, "sort", &[0; 9], &params).unwrap());
+
+        // Same seed, another secret: only the matching public half verifies
+        let mut other =
+            DeterministicEngine::new(Some(42), &[8; 32]).with_domain_tag("deployment-a");
+        let other_result = other.compute("sort", &[0; 8]).unwrap();
+        let other_params = other.export_verification_params();
+        assert_ne!(other_params.public_key, params.public_key);
+        assert!(verify_with_params(&other_result, "sort", &[0; 8], &other_params).unwrap());
+        assert!(!verify_with_params(&other_result, "sort", &[0; 8], &params).unwrap());
+        assert!(!verify_with_params(&result, "sort", &[0; 8], &other_params).unwrap());
+
+        let other_tag = VerificationParams {
+            domain_tag: hex::encode(DEFAULT_PROOF_DOMAIN_TAG),
+            ..params.clone()
+        };
+        assert!(!verify_with_params(&result, "sort", &[0; 8], &other_tag).unwrap());
+        let future = VerificationParams {
+            proof_version: 3,
+            ..params
+        };
+        assert!(verify_with_params(&result, "sort", &[0; 8], &future).is_err());
+    }
+
+    #[test]
+    fn test_reference_attestation_binds_uri_to_content_digest() {
+        use deterministic_engine::verifier::engine::VerifierEngine;
+
+        let content = b"release artifact bytes stored elsewhere";
+        let mut input = Sha256::digest(content).to_vec();
+        input.extend_from_slice(&1_700_000_000u64.to_be_bytes());
+        input.extend_from_slice(b"https://artifacts.example.com/release-1.2.0.tar.gz");
+
+        let mut engine = DeterministicEngine::new(Some(42), &TEST_SIGNING_KEY);
+        let result = engine.compute("attest_reference", &input).unwrap();
+        let reference = ReferenceAttestation::parse(&result.value).unwrap();
+        assert_eq!(
+            reference.uri,
+            "https://artifacts.example.com/release-1.2.0.tar.gz"
+        );
+        assert_eq!(reference.attested_at, 1_700_000_000);
+
+        let key = RootSigningKey::from_seed(&TEST_SIGNING_KEY);
+        let verifier = VerifierEngine::new(key.public_key(), 42);
+        assert!(verifier.verify_reference(&result, content).unwrap());
+        assert!(!verifier
+            .verify_reference(&result, b"substituted artifact")
+            .unwrap());
+
+        // Pointing the signed value at another URI breaks the proof
+        let mut moved = result.clone();
+        moved.value.extend_from_slice(b".old");
+        assert!(!verifier.verify_reference(&moved, content).unwrap());
+
+        assert!(engine.compute("attest_reference", &input[..40]).is_err());
+        assert!(engine.compute("attest_reference", &input[..20]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod property_tests {
+    use super::*;
+    use clock::FixedClock;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use tests::{encode_f64_list, encode_u64_list, TEST_SIGNING_KEY};
+
+    /// Arbitrary well-formed `(operation, input)` pairs with bounded sizes
+    fn operation_input() -> impl Strategy<Value = (&'static str, Vec<u8>)> {
+        let hash = vec(any::<u8>(), 0..256).prop_map(|data| ("hash", data));
+        let sign = vec(any::<u8>(), 0..256).prop_map(|data| ("sign", data));
+        let encrypt = vec(any::<u8>(), 0..256).prop_map(|data| ("encrypt", data));
+        let crc = (vec(any::<u8>(), 0..256), any::<bool>())
+            .prop_map(|(data, wide)| (if wide { "crc64" } else { "crc32" }, data));
+        let rle = vec(prop_oneof![Just(0u8), Just(1u8), any::<u8>()], 0..512)
+            .prop_map(|data| ("rle_encode", data));
+        let byte_diff = (vec(0u8..4, 0..64), vec(0u8..4, 0..64)).prop_map(|(old, new)| {
+            let mut input = Vec::new();
+            for string in [old, new] {
+                input.extend_from_slice(&(string.len() as u32).to_be_bytes());
+                input.extend(string);
+            }
+            ("byte_diff", input)
+        });
+        let reference = (any::<[u8; 32]>(), any::<u64>(), "[a-z]{1,16}").prop_map(
+            |(digest, attested_at, path)| {
+                let mut input = digest.to_vec();
+                input.extend_from_slice(&attested_at.to_be_bytes());
+                input.extend_from_slice(format!("https://example.com/{}", path).as_bytes());
+                ("attest_reference", input)
+            },
+        );
+        let text_encode = (vec(any::<u8>(), 0..256), any::<bool>()).prop_map(|(data, base32)| {
+            (
+                if base32 {
+                    "base32_encode"
+                } else {
+                    "base64_encode"
+                },
+                data,
+            )
+        });
+        let fibonacci = (0u8..=93).prop_map(|n| ("fibonacci", vec![n]));
+        let fibonacci_sequence = (0u8..=93).prop_map(|n| ("fibonacci_sequence", vec![n]));
+        let factorize = (1u64..=1 << 24).prop_map(|n| ("factorize", n.to_be_bytes().to_vec()));
+        let polynomial =
+            (0u32..16, -10.0f64..10.0, vec(any::<u8>(), 4..64)).prop_map(|(degree, x, tail)| {
+                let mut input = Vec::new();
+                input.extend_from_slice(&degree.to_be_bytes());
+                input.extend_from_slice(&x.to_be_bytes());
+                input.extend_from_slice(&tail);
+                ("polynomial_eval", input)
+            });
+        let matrix = (1u32..6, 1u32..6)
+            .prop_flat_map(|(rows, cols)| {
+                (
+                    Just(rows),
+                    Just(cols),
+                    vec(-1e6f64..1e6, (rows * cols) as usize),
+                )
+            })
+            .prop_map(|(rows, cols, values)| {
+                let mut input = Vec::new();
+                input.extend_from_slice(&rows.to_be_bytes());
+                input.extend_from_slice(&cols.to_be_bytes());
+                for value in values {
+                    input.extend_from_slice(&value.to_be_bytes());
+                }
+                ("matrix_multiply", input)
+            });
+        let dot_product = (0usize..16)
+            .prop_flat_map(|n| (vec(-1e6f64..1e6, n), vec(-1e6f64..1e6, n)))
+            .prop_map(|(a, b)| {
+                (
+                    "dot_product",
+                    [encode_f64_list(&a), encode_f64_list(&b)].concat(),
+                )
+            });
+        // A non-zero first value keeps the vector normalizable
+        let normalize_vector = (
+            prop_oneof![-1e6f64..-1e-3, 1e-3f64..1e6],
+            vec(-1e6f64..1e6, 0..16),
+        )
+            .prop_map(|(first, rest)| {
+                (
+                    "normalize_vector",
+                    encode_f64_list(&[&[first][..], &rest].concat()),
+                )
+            });
+        let moving_average = (1usize..8)
+            .prop_flat_map(|window| (Just(window), vec(-1e6f64..1e6, window..window + 16)))
+            .prop_map(|(window, series)| {
+                (
+                    "moving_average",
+                    [
+                        &(window as u32).to_be_bytes()[..],
+                        &encode_f64_list(&series),
+                    ]
+                    .concat(),
+                )
+            });
+        // Diagonally dominant, so never singular
+        let lu = (1u32..6)
+            .prop_flat_map(|n| (Just(n), vec(-1.0f64..1.0, (n * n) as usize)))
+            .prop_map(|(n, values)| {
+                let mut input = Vec::new();
+                input.extend_from_slice(&n.to_be_bytes());
+                input.extend_from_slice(&n.to_be_bytes());
+                for (i, value) in values.into_iter().enumerate() {
+                    let diagonal = if i % (n as usize + 1) == 0 {
+                        n as f64
+                    } else {
+                        0.0
+                    };
+                    input.extend_from_slice(&(value + diagonal).to_be_bytes());
+                }
+                ("matrix_lu", input)
+            });
+        let kmeans = (1u32..4, 1u32..5, 0u32..16)
+            .prop_flat_map(|(dims, centroids, points)| {
+                (
+                    Just((dims, centroids, points)),
+                    vec(-1e3f64..1e3, ((centroids + points) * dims) as usize),
+                )
+            })
+            .prop_map(|((dims, centroids, points), values)| {
+                let mut input = Vec::new();
+                for header in [dims, centroids, points] {
+                    input.extend_from_slice(&header.to_be_bytes());
+                }
+                input.extend(values.into_iter().flat_map(f64::to_be_bytes));
+                ("kmeans_assign", input)
+            });
+        let sort = vec((any::<u64>(), any::<u8>()), 0..32).prop_map(|records| {
+            let mut input = Vec::new();
+            input.extend_from_slice(&(records.len() as u32).to_be_bytes());
+            input.extend_from_slice(&1u32.to_be_bytes());
+            for (key, payload) in records {
+                input.extend_from_slice(&key.to_be_bytes());
+                input.push(payload);
+            }
+            ("sort", input)
+        });
+        let shuffle =
+            vec(any::<u64>(), 0..32).prop_map(|values| ("shuffle", encode_u64_list(&values)));
+
+        let base_convert = (2u32..=36, 2u8..=36)
+            .prop_flat_map(|(from, to)| (Just(from), Just(to), vec(0..from, 1..40)))
+            .prop_map(|(from, to, digits)| {
+                let mut input = vec![from as u8, to];
+                input.extend(
+                    digits
+                        .into_iter()
+                        .map(|d| char::from_digit(d, from).unwrap() as u8),
+                );
+                ("base_convert", input)
+            });
+
+        let k_of_n =
+            (0u32..8, vec(any::<u64>(), 0..32), 0usize..3).prop_map(|(k, values, operation)| {
+                let input = [&k.to_be_bytes()[..], &encode_u64_list(&values)].concat();
+                (["sample", "stratified_sample", "top_k"][operation], input)
+            });
+
+        let rolling_hash = (1u8..=8, vec(any::<u8>(), 0..256)).prop_map(|(mask_bits, data)| {
+            let mut input = vec![mask_bits];
+            input.extend(data);
+            ("rolling_hash", input)
+        });
+
+        let set_operands = (
+            vec(any::<u64>(), 0..16),
+            vec(any::<u64>(), 0..16),
+            0usize..3,
+        )
+            .prop_map(|(mut left, mut right, operation)| {
+                left.sort_unstable();
+                right.sort_unstable();
+                let input = [encode_u64_list(&left), encode_u64_list(&right)].concat();
+                (
+                    ["set_union", "set_intersection", "merge_sorted"][operation],
+                    input,
+                )
+            });
+
+        let histogram = (1u32..16, vec(any::<u64>(), 0..32)).prop_map(|(buckets, values)| {
+            (
+                "histogram",
+                [&buckets.to_be_bytes()[..], &encode_u64_list(&values)].concat(),
+            )
+        });
+
+        let quantile = (0.0f64..=1.0, vec(-1e6f64..1e6, 1..32)).prop_map(|(q, values)| {
+            (
+                "quantile",
+                [&q.to_be_bytes()[..], &encode_f64_list(&values)].concat(),
+            )
+        });
+
+        let normalize_utf8 =
+            ".{0,32}".prop_map(|text: String| ("normalize_utf8", text.into_bytes()));
+
+        prop_oneof![
+            hash,
+            sign,
+            encrypt,
+            fibonacci,
+            fibonacci_sequence,
+            factorize,
+            polynomial,
+            matrix,
+            lu,
+            dot_product,
+            normalize_vector,
+            moving_average,
+            kmeans,
+            sort,
+            shuffle,
+            base_convert,
+            k_of_n,
+            rolling_hash,
+            set_operands,
+            histogram,
+            quantile,
+            normalize_utf8,
+            crc,
+            text_encode,
+            rle,
+            byte_diff,
+            reference
+        ]
+    }
+
+    // No decrypt(encrypt(x)) == x property: the engine has no decrypt
+    // operation, and the crypto module it would come from is not in this tree.
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_compute_then_verify((operation, input) in operation_input(), seed in any::<u64>()) {
+            let mut engine = DeterministicEngine::new(Some(seed), &TEST_SIGNING_KEY);
+            let result = engine.compute(operation, &input).unwrap();
+            prop_assert!(engine.verify_result(&result, operation, &input).unwrap());
+        }
+
+        #[test]
+        fn prop_same_seed_same_result((operation, input) in operation_input(), seed in any::<u64>()) {
+            let mut first = DeterministicEngine::new(Some(seed), &TEST_SIGNING_KEY).with_clock(FixedClock(0));
+            let mut second = DeterministicEngine::new(Some(seed), &TEST_SIGNING_KEY).with_clock(FixedClock(0));
+            let a = first.compute(operation, &input).unwrap();
+            let b = second.compute(operation, &input).unwrap();
+            prop_assert_eq!(a.value, b.value);
+            prop_assert_eq!(a.proof_hash, b.proof_hash);
+            prop_assert_eq!(a.verification_signature, b.verification_signature);
+        }
+    }
+}
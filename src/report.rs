@@ -0,0 +1,137 @@
+//! Console status output with an ASCII fallback
+//!
+//! Status lines carry a marker such as a tick or a rocket. Emoji markers are
+//! only used when stdout is a terminal with a UTF-8 locale; pipes, log files
+//! and CI captures get plain ASCII so they never show mojibake.
+
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Write};
+
+/// Marker printed in front of a status line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symbol {
+    Start,
+    Pass,
+    Fail,
+    Done,
+    Stats,
+    Export,
+    Engine,
+}
+
+impl Symbol {
+    /// Every marker, for exhaustive output checks
+    #[cfg(test)]
+    pub const ALL: [Symbol; 7] = [
+        Symbol::Start,
+        Symbol::Pass,
+        Symbol::Fail,
+        Symbol::Done,
+        Symbol::Stats,
+        Symbol::Export,
+        Symbol::Engine,
+    ];
+
+    fn emoji(self) -> &'static str {
+        match self {
+            Symbol::Start => "🚀",
+            Symbol::Pass => "✓",
+            Symbol::Fail => "✗",
+            Symbol::Done => "✅",
+            Symbol::Stats => "📊",
+            Symbol::Export => "📋",
+            Symbol::Engine => "🔒",
+        }
+    }
+
+    fn ascii(self) -> &'static str {
+        match self {
+            Symbol::Start => "==>",
+            Symbol::Pass => "[ok]",
+            Symbol::Fail => "[FAIL]",
+            Symbol::Done => "[done]",
+            Symbol::Stats => "[stats]",
+            Symbol::Export => "[export]",
+            Symbol::Engine => "[engine]",
+        }
+    }
+}
+
+/// Writes status lines, choosing emoji or ASCII markers once up front
+pub struct Reporter<W: Write> {
+    out: W,
+    emoji: bool,
+}
+
+impl Reporter<io::Stdout> {
+    /// Reporter on stdout, using emoji only for a UTF-8 terminal
+    pub fn stdout() -> Self {
+        let stdout = io::stdout();
+        let emoji = stdout.is_terminal() && locale_is_utf8();
+        Self::new(stdout, emoji)
+    }
+}
+
+impl<W: Write> Reporter<W> {
+    /// Reporter on an arbitrary writer
+    pub fn new(out: W, emoji: bool) -> Self {
+        Self { out, emoji }
+    }
+
+    /// Write `message` prefixed with the marker for `symbol`
+    pub fn status(&mut self, symbol: Symbol, message: impl Display) -> io::Result<()> {
+        let marker = if self.emoji {
+            symbol.emoji()
+        } else {
+            symbol.ascii()
+        };
+        writeln!(self.out, "{} {}", marker, message)
+    }
+
+    /// Write `message` without a marker
+    pub fn line(&mut self, message: impl Display) -> io::Result<()> {
+        writeln!(self.out, "{}", message)
+    }
+
+    /// Recover the underlying writer
+    #[cfg(test)]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Whether the locale environment advertises UTF-8, checked in POSIX precedence
+fn locale_is_utf8() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .is_some_and(|value| {
+            let value = value.to_ascii_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_tty_output_is_ascii() {
+        let mut reporter = Reporter::new(Vec::new(), false);
+        for symbol in Symbol::ALL {
+            reporter.status(symbol, "status").unwrap();
+        }
+        reporter.line("plain line").unwrap();
+
+        let output = reporter.into_inner();
+        assert!(
+            output.is_ascii(),
+            "non-ASCII output: {}",
+            String::from_utf8_lossy(&output)
+        );
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .starts_with("==> status\n"));
+    }
+}
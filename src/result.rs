@@ -0,0 +1,141 @@
+//! Computation results and the proofs that bind them to their inputs
+//!
+//! A result's proof hash is `proof_v2` over the operation, input, value and
+//! seed, and its signature covers that proof hash. Everything here is shared
+//! by the engine that produces results and by verifiers that only check them.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::verifier::attestation::Signature;
+
+/// Domain-separation tag folded into proofs unless a deployment sets its own
+pub const DEFAULT_PROOF_DOMAIN_TAG: &[u8] = b"DETERMINISTIC_PROOF_V2";
+
+/// Proof scheme results are produced under; see `proof_v2`
+pub const PROOF_VERSION: u32 = 2;
+
+/// Hash function behind `proof_v2`, whatever the `"hash"` operation uses
+pub const PROOF_HASH: &str = "sha256";
+
+/// Core deterministic computation result with cryptographic proof
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComputationResult {
+    pub value: Vec<u8>,
+    pub proof_hash: String,
+    pub timestamp: u64,
+    pub seed: u64,
+    pub verification_signature: Signature,
+    pub execution_time_ns: u64,
+    /// Thread CPU time spent computing, when CPU-time measurement is enabled
+    #[serde(default)]
+    pub cpu_time_ns: Option<u64>,
+}
+
+impl ComputationResult {
+    /// Borrow the raw result bytes
+    pub fn value_bytes(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Borrow the hex-encoded proof hash
+    pub fn proof_hash_str(&self) -> &str {
+        &self.proof_hash
+    }
+
+    /// Interpret an 8-byte big-endian result (e.g. `fibonacci`) as a `u64`
+    ///
+    /// Returns `None` for results of any other length, such as 32-byte hashes.
+    pub fn as_u64(&self) -> Option<u64> {
+        let bytes: [u8; 8] = self.value.as_slice().try_into().ok()?;
+        Some(u64::from_be_bytes(bytes))
+    }
+
+    /// Bytes held on the heap by the result's value and proof hash
+    ///
+    /// Lengths rather than capacities are summed so the figure is the same on
+    /// every run, which keeps byte-budget eviction deterministic. The
+    /// signature is stored inline and is not counted.
+    pub fn heap_size(&self) -> usize {
+        self.value.len() + self.proof_hash.len()
+    }
+}
+
+/// Content digest bound to a URI by an `attest_reference` result
+///
+/// The encoding is the SHA-256 digest (32 bytes), the attested time (u64 BE
+/// Unix seconds) and then the URI as UTF-8 to the end. The operation's input
+/// and output are both this encoding, so the signed value carries the whole
+/// binding and the content itself never passes through the engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceAttestation {
+    pub digest: [u8; 32],
+    pub attested_at: u64,
+    pub uri: String,
+}
+
+impl ReferenceAttestation {
+    /// Decodes an `attest_reference` input or result value
+    pub fn parse(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let digest: [u8; 32] = bytes
+            .get(..32)
+            .and_then(|digest| digest.try_into().ok())
+            .ok_or("Reference is shorter than its digest")?;
+        let attested_at = bytes
+            .get(32..40)
+            .map(|time| u64::from_be_bytes(time.try_into().unwrap()))
+            .ok_or("Reference is shorter than its timestamp")?;
+        let uri = std::str::from_utf8(&bytes[40..])
+            .map_err(|e| format!("Reference URI is not UTF-8: {}", e))?;
+        if uri.is_empty() {
+            return Err("Reference URI is empty".into());
+        }
+        Ok(Self {
+            digest,
+            attested_at,
+            uri: uri.to_string(),
+        })
+    }
+
+    /// Whether `content` is what the reference points at
+    pub fn matches(&self, content: &[u8]) -> bool {
+        Sha256::digest(content).as_slice() == self.digest
+    }
+}
+
+/// Derive a v2 proof hash
+///
+/// Each variable-length field is preceded by its length as a u64 BE, so no
+/// two (operation, input, result) triples share an encoding. A non-empty
+/// context follows the seed, also length-prefixed, so context-free proofs
+/// keep their existing value.
+pub fn proof_v2(
+    domain_tag: &[u8],
+    operation: &str,
+    input: &[u8],
+    result: &[u8],
+    seed: u64,
+    context: &[u8],
+) -> String {
+    let mut hasher = Sha256::new();
+    for field in [domain_tag, operation.as_bytes(), input, result] {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field);
+    }
+    hasher.update(seed.to_be_bytes());
+    if !context.is_empty() {
+        hasher.update((context.len() as u64).to_be_bytes());
+        hasher.update(context);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Operation name a proof is taken over, marking truncated `"hash"` digests
+pub fn proof_operation(operation: &str, hash_truncation: Option<usize>) -> Cow<'_, str> {
+    match hash_truncation {
+        Some(len) if operation == "hash" => Cow::Owned(format!("hash@truncate-{}", len)),
+        _ => Cow::Borrowed(operation),
+    }
+}
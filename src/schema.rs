@@ -0,0 +1,503 @@
+//! Machine-readable byte layouts for the built-in operations
+//!
+//! Each schema lists the fields of an operation's input and output in wire
+//! order, so clients (or an HTTP layer in front of the engine) can build and
+//! validate payloads without reading the operation source.
+
+use serde::Serialize;
+
+/// How a single field value is encoded on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    U8,
+    U32Be,
+    U64Be,
+    F64Be,
+    /// Opaque bytes whose length comes from the field's `count`
+    Bytes,
+    /// ASCII digits `0-9a-z`, case-insensitive on input
+    AsciiDigits,
+}
+
+impl Encoding {
+    /// Width of one value in bytes, or `None` for variable-length encodings
+    pub const fn size(self) -> Option<usize> {
+        match self {
+            Encoding::U8 => Some(1),
+            Encoding::U32Be => Some(4),
+            Encoding::U64Be | Encoding::F64Be => Some(8),
+            Encoding::Bytes | Encoding::AsciiDigits => None,
+        }
+    }
+}
+
+/// How many values a field holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Count {
+    One,
+    /// Exactly this many values, e.g. the 32 bytes of a digest
+    Fixed(usize),
+    /// Product of the named header fields, e.g. `rows * cols`
+    Product(&'static [&'static str]),
+    /// Everything up to the end of the buffer
+    Remaining,
+}
+
+/// One field of an input or output layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Field {
+    pub name: &'static str,
+    pub encoding: Encoding,
+    /// Width of one value in bytes, mirroring `Encoding::size`
+    pub size: Option<usize>,
+    pub count: Count,
+    pub description: &'static str,
+}
+
+impl Field {
+    const fn new(
+        name: &'static str,
+        encoding: Encoding,
+        count: Count,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            encoding,
+            size: encoding.size(),
+            count,
+            description,
+        }
+    }
+}
+
+/// Input and output layout of one operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OperationSchema {
+    pub operation: &'static str,
+    pub input: &'static [Field],
+    pub output: &'static [Field],
+}
+
+#[cfg(test)]
+impl OperationSchema {
+    /// Looks up an input field by name
+    pub fn input_field(&self, name: &str) -> Option<&Field> {
+        self.input.iter().find(|field| field.name == name)
+    }
+}
+
+const OPAQUE_INPUT: &[Field] = &[Field::new(
+    "data",
+    Encoding::Bytes,
+    Count::Remaining,
+    "arbitrary input bytes",
+)];
+
+const U64_LIST: &[Field] = &[
+    Field::new("count", Encoding::U32Be, Count::One, "number of values"),
+    Field::new(
+        "values",
+        Encoding::U64Be,
+        Count::Product(&["count"]),
+        "the values",
+    ),
+];
+
+const REFERENCE: &[Field] = &[
+    Field::new(
+        "digest",
+        Encoding::Bytes,
+        Count::Fixed(32),
+        "SHA-256 of the referenced content",
+    ),
+    Field::new(
+        "attested_at",
+        Encoding::U64Be,
+        Count::One,
+        "Unix seconds the reference is attested for",
+    ),
+    Field::new(
+        "uri",
+        Encoding::Bytes,
+        Count::Remaining,
+        "non-empty UTF-8 URI of the content",
+    ),
+];
+
+const SET_OPERANDS: &[Field] = &[
+    Field::new(
+        "left_count",
+        Encoding::U32Be,
+        Count::One,
+        "number of values in the first set",
+    ),
+    Field::new(
+        "left",
+        Encoding::U64Be,
+        Count::Product(&["left_count"]),
+        "first set, ascending",
+    ),
+    Field::new(
+        "right_count",
+        Encoding::U32Be,
+        Count::One,
+        "number of values in the second set",
+    ),
+    Field::new(
+        "right",
+        Encoding::U64Be,
+        Count::Product(&["right_count"]),
+        "second set, ascending",
+    ),
+];
+
+const SCHEMAS: &[OperationSchema] = &[
+    OperationSchema {
+        operation: "hash",
+        input: OPAQUE_INPUT,
+        output: &[Field::new("digest", Encoding::Bytes, Count::Remaining, "seeded digest; length set by the engine's hasher")],
+    },
+    OperationSchema {
+        operation: "encrypt",
+        input: OPAQUE_INPUT,
+        output: &[Field::new("ciphertext", Encoding::Bytes, Count::Remaining, "backend-defined ciphertext")],
+    },
+    OperationSchema {
+        operation: "sign",
+        input: OPAQUE_INPUT,
+        output: &[Field::new("signature", Encoding::Bytes, Count::Remaining, "backend-defined signature")],
+    },
+    OperationSchema {
+        operation: "matrix_multiply",
+        input: &[
+            Field::new("rows", Encoding::U32Be, Count::One, "number of rows"),
+            Field::new("cols", Encoding::U32Be, Count::One, "number of columns"),
+            Field::new("cells", Encoding::F64Be, Count::Product(&["rows", "cols"]), "values in row-major order"),
+        ],
+        output: &[
+            Field::new("rows", Encoding::U32Be, Count::One, "rows of the transpose (input cols)"),
+            Field::new("cols", Encoding::U32Be, Count::One, "columns of the transpose (input rows)"),
+            Field::new("cells", Encoding::F64Be, Count::Product(&["rows", "cols"]), "transposed values in row-major order"),
+        ],
+    },
+    OperationSchema {
+        operation: "matrix_lu",
+        input: &[
+            Field::new("rows", Encoding::U32Be, Count::One, "number of rows"),
+            Field::new("cols", Encoding::U32Be, Count::One, "number of columns, equal to rows"),
+            Field::new("cells", Encoding::F64Be, Count::Product(&["rows", "cols"]), "finite values in row-major order"),
+        ],
+        output: &[
+            Field::new("n", Encoding::U32Be, Count::One, "matrix order"),
+            Field::new("permutation", Encoding::U32Be, Count::Product(&["n"]), "source row of each row of P * A"),
+            Field::new("lower", Encoding::F64Be, Count::Product(&["n", "n"]), "unit lower triangular L, row-major"),
+            Field::new("upper", Encoding::F64Be, Count::Product(&["n", "n"]), "upper triangular U, row-major"),
+        ],
+    },
+    OperationSchema {
+        operation: "dot_product",
+        input: &[
+            Field::new("a_len", Encoding::U32Be, Count::One, "length of the first vector"),
+            Field::new("a", Encoding::F64Be, Count::Product(&["a_len"]), "first vector, finite values"),
+            Field::new("b_len", Encoding::U32Be, Count::One, "length of the second vector, equal to a_len"),
+            Field::new("b", Encoding::F64Be, Count::Product(&["b_len"]), "second vector, finite values"),
+        ],
+        output: &[Field::new("value", Encoding::F64Be, Count::One, "sum of a[i] * b[i], accumulated left to right")],
+    },
+    OperationSchema {
+        operation: "normalize_vector",
+        input: &[
+            Field::new("count", Encoding::U32Be, Count::One, "length of the vector"),
+            Field::new("values", Encoding::F64Be, Count::Product(&["count"]), "finite values, not all zero"),
+        ],
+        output: &[
+            Field::new("count", Encoding::U32Be, Count::One, "length of the vector"),
+            Field::new("values", Encoding::F64Be, Count::Product(&["count"]), "values scaled to unit L2 norm"),
+        ],
+    },
+    OperationSchema {
+        operation: "moving_average",
+        input: &[
+            Field::new("window", Encoding::U32Be, Count::One, "values per average, 1 to count"),
+            Field::new("count", Encoding::U32Be, Count::One, "length of the series"),
+            Field::new("series", Encoding::F64Be, Count::Product(&["count"]), "finite values"),
+        ],
+        output: &[
+            Field::new("count", Encoding::U32Be, Count::One, "number of averages, count - window + 1"),
+            Field::new("averages", Encoding::F64Be, Count::Product(&["count"]), "mean of each full window, in series order"),
+        ],
+    },
+    OperationSchema {
+        operation: "kmeans_assign",
+        input: &[
+            Field::new("dims", Encoding::U32Be, Count::One, "dimensions per vector, at least 1"),
+            Field::new("centroid_count", Encoding::U32Be, Count::One, "number of centroids, at least 1"),
+            Field::new("point_count", Encoding::U32Be, Count::One, "number of points"),
+            Field::new("centroids", Encoding::F64Be, Count::Product(&["centroid_count", "dims"]), "finite values, one centroid per row"),
+            Field::new("points", Encoding::F64Be, Count::Product(&["point_count", "dims"]), "finite values, one point per row"),
+        ],
+        output: &[
+            Field::new("count", Encoding::U32Be, Count::One, "number of points"),
+            Field::new(
+                "assignments",
+                Encoding::U32Be,
+                Count::Product(&["count"]),
+                "index of the nearest centroid by squared Euclidean distance, lowest on ties",
+            ),
+        ],
+    },
+    OperationSchema {
+        operation: "polynomial_eval",
+        input: &[
+            Field::new("degree", Encoding::U32Be, Count::One, "polynomial degree"),
+            Field::new("x", Encoding::F64Be, Count::One, "evaluation point"),
+            Field::new("coefficient_seed", Encoding::Bytes, Count::Remaining, "at least 4 bytes hashed into the coefficients"),
+        ],
+        output: &[Field::new("value", Encoding::F64Be, Count::One, "polynomial evaluated at x")],
+    },
+    OperationSchema {
+        operation: "fibonacci",
+        input: &[Field::new("n", Encoding::U8, Count::One, "index, at most 93")],
+        output: &[Field::new("value", Encoding::U64Be, Count::One, "F(n)")],
+    },
+    OperationSchema {
+        operation: "fibonacci_sequence",
+        input: &[Field::new("n", Encoding::U8, Count::One, "last index, at most 93")],
+        output: &[Field::new("terms", Encoding::U64Be, Count::Remaining, "F(0) through F(n)")],
+    },
+    OperationSchema {
+        operation: "factorize",
+        input: &[Field::new("n", Encoding::U64Be, Count::One, "value to factor, 1 to 2^40")],
+        output: &[Field::new("factors", Encoding::U64Be, Count::Remaining, "prime, exponent pairs, primes ascending")],
+    },
+    OperationSchema {
+        operation: "sort",
+        input: &[
+            Field::new("count", Encoding::U32Be, Count::One, "number of records"),
+            Field::new("width", Encoding::U32Be, Count::One, "payload bytes per record"),
+            Field::new("records", Encoding::Bytes, Count::Remaining, "count records of a u64 BE key then width payload bytes"),
+        ],
+        output: &[
+            Field::new("count", Encoding::U32Be, Count::One, "number of records"),
+            Field::new("width", Encoding::U32Be, Count::One, "payload bytes per record"),
+            Field::new("records", Encoding::Bytes, Count::Remaining, "records stably sorted by key"),
+        ],
+    },
+    OperationSchema {
+        operation: "shuffle",
+        input: U64_LIST,
+        output: U64_LIST,
+    },
+    OperationSchema {
+        operation: "base_convert",
+        input: &[
+            Field::new("source_radix", Encoding::U8, Count::One, "radix of the input digits, 2..=36"),
+            Field::new("target_radix", Encoding::U8, Count::One, "radix of the output digits, 2..=36"),
+            Field::new("digits", Encoding::AsciiDigits, Count::Remaining, "integer, most significant digit first"),
+        ],
+        output: &[Field::new("digits", Encoding::AsciiDigits, Count::Remaining, "lowercase digits without leading zeros")],
+    },
+    OperationSchema {
+        operation: "sample",
+        input: &[
+            Field::new("k", Encoding::U32Be, Count::One, "sample size"),
+            Field::new("count", Encoding::U32Be, Count::One, "number of values"),
+            Field::new("values", Encoding::U64Be, Count::Product(&["count"]), "population to sample from"),
+        ],
+        output: U64_LIST,
+    },
+    OperationSchema {
+        operation: "stratified_sample",
+        input: &[
+            Field::new("k", Encoding::U32Be, Count::One, "sample size per quartile"),
+            Field::new("count", Encoding::U32Be, Count::One, "number of values"),
+            Field::new("values", Encoding::U64Be, Count::Product(&["count"]), "population to sample from"),
+        ],
+        output: &[
+            Field::new("q1_count", Encoding::U32Be, Count::One, "values kept from the lowest quartile"),
+            Field::new("q1", Encoding::U64Be, Count::Product(&["q1_count"]), "sample of the lowest quartile"),
+            Field::new("q2_count", Encoding::U32Be, Count::One, "values kept from the second quartile"),
+            Field::new("q2", Encoding::U64Be, Count::Product(&["q2_count"]), "sample of the second quartile"),
+            Field::new("q3_count", Encoding::U32Be, Count::One, "values kept from the third quartile"),
+            Field::new("q3", Encoding::U64Be, Count::Product(&["q3_count"]), "sample of the third quartile"),
+            Field::new("q4_count", Encoding::U32Be, Count::One, "values kept from the highest quartile"),
+            Field::new("q4", Encoding::U64Be, Count::Product(&["q4_count"]), "sample of the highest quartile"),
+        ],
+    },
+    OperationSchema {
+        operation: "top_k",
+        input: &[
+            Field::new("k", Encoding::U32Be, Count::One, "number of values to keep"),
+            Field::new("count", Encoding::U32Be, Count::One, "number of values"),
+            Field::new("values", Encoding::U64Be, Count::Product(&["count"]), "values to rank; ties favour earlier values"),
+        ],
+        output: &[
+            Field::new("count", Encoding::U32Be, Count::One, "number of values kept, min(k, count)"),
+            Field::new("values", Encoding::U64Be, Count::Product(&["count"]), "largest values, descending"),
+        ],
+    },
+    OperationSchema {
+        operation: "rolling_hash",
+        input: &[
+            Field::new("mask_bits", Encoding::U8, Count::One, "boundary mask width, 1..=32"),
+            Field::new("data", Encoding::Bytes, Count::Remaining, "content to chunk"),
+        ],
+        output: &[
+            Field::new("count", Encoding::U32Be, Count::One, "number of boundaries"),
+            Field::new("boundaries", Encoding::U64Be, Count::Product(&["count"]), "exclusive chunk end offsets"),
+        ],
+    },
+    OperationSchema {
+        operation: "set_union",
+        input: SET_OPERANDS,
+        output: U64_LIST,
+    },
+    OperationSchema {
+        operation: "set_intersection",
+        input: SET_OPERANDS,
+        output: U64_LIST,
+    },
+    OperationSchema {
+        operation: "merge_sorted",
+        input: SET_OPERANDS,
+        output: &[
+            Field::new("count", Encoding::U32Be, Count::One, "left_count + right_count"),
+            Field::new("values", Encoding::U64Be, Count::Product(&["count"]), "every value, ascending; ties take the first list first"),
+        ],
+    },
+    OperationSchema {
+        operation: "histogram",
+        input: &[
+            Field::new("buckets", Encoding::U32Be, Count::One, "number of equal-width buckets, 1..=65536"),
+            Field::new("count", Encoding::U32Be, Count::One, "number of values"),
+            Field::new("values", Encoding::U64Be, Count::Product(&["count"]), "values to bucket"),
+        ],
+        output: &[
+            Field::new("buckets", Encoding::U32Be, Count::One, "number of buckets"),
+            Field::new("counts", Encoding::U64Be, Count::Product(&["buckets"]), "values per bucket, lowest range first"),
+        ],
+    },
+    OperationSchema {
+        operation: "quantile",
+        input: &[
+            Field::new("q", Encoding::F64Be, Count::One, "quantile in 0..=1"),
+            Field::new("count", Encoding::U32Be, Count::One, "number of values, at least 1"),
+            Field::new("values", Encoding::F64Be, Count::Product(&["count"]), "finite values in any order"),
+        ],
+        output: &[Field::new("value", Encoding::F64Be, Count::One, "linearly interpolated quantile (Hyndman-Fan type 7)")],
+    },
+    OperationSchema {
+        operation: "normalize_utf8",
+        input: &[Field::new("text", Encoding::Bytes, Count::Remaining, "UTF-8 text")],
+        output: &[Field::new("text", Encoding::Bytes, Count::Remaining, "the text in Unicode NFC, as UTF-8")],
+    },
+    OperationSchema {
+        operation: "crc32",
+        input: OPAQUE_INPUT,
+        output: &[Field::new("checksum", Encoding::U32Be, Count::One, "CRC-32/ISO-HDLC of the seed then the input; not secure")],
+    },
+    OperationSchema {
+        operation: "crc64",
+        input: OPAQUE_INPUT,
+        output: &[Field::new("checksum", Encoding::U64Be, Count::One, "CRC-64/XZ of the seed then the input; not secure")],
+    },
+    OperationSchema {
+        operation: "base64_encode",
+        input: OPAQUE_INPUT,
+        output: &[Field::new("text", Encoding::Bytes, Count::Remaining, "padded RFC 4648 Base64, standard alphabet")],
+    },
+    OperationSchema {
+        operation: "base64_decode",
+        input: &[Field::new("text", Encoding::Bytes, Count::Remaining, "canonical padded RFC 4648 Base64")],
+        output: &[Field::new("data", Encoding::Bytes, Count::Remaining, "decoded bytes")],
+    },
+    OperationSchema {
+        operation: "base32_encode",
+        input: OPAQUE_INPUT,
+        output: &[Field::new("text", Encoding::Bytes, Count::Remaining, "padded RFC 4648 Base32, standard alphabet")],
+    },
+    OperationSchema {
+        operation: "base32_decode",
+        input: &[Field::new("text", Encoding::Bytes, Count::Remaining, "canonical padded RFC 4648 Base32")],
+        output: &[Field::new("data", Encoding::Bytes, Count::Remaining, "decoded bytes")],
+    },
+    OperationSchema {
+        operation: "rle_encode",
+        input: OPAQUE_INPUT,
+        output: &[Field::new("pairs", Encoding::Bytes, Count::Remaining, "(count u8 1..=255, byte) pairs, one or more per run")],
+    },
+    OperationSchema {
+        operation: "rle_decode",
+        input: &[Field::new("pairs", Encoding::Bytes, Count::Remaining, "canonical rle_encode output")],
+        output: &[Field::new("data", Encoding::Bytes, Count::Remaining, "decoded bytes")],
+    },
+    OperationSchema {
+        operation: "byte_diff",
+        input: &[
+            Field::new("old_len", Encoding::U32Be, Count::One, "length of the old string"),
+            Field::new("old", Encoding::Bytes, Count::Product(&["old_len"]), "the old string"),
+            Field::new("new_len", Encoding::U32Be, Count::One, "length of the new string"),
+            Field::new("new", Encoding::Bytes, Count::Product(&["new_len"]), "the new string"),
+        ],
+        output: &[Field::new(
+            "ops",
+            Encoding::Bytes,
+            Count::Remaining,
+            "ops of a tag (0 copy, 1 delete, 2 insert) and a u32 BE length; inserts carry their bytes",
+        )],
+    },
+    OperationSchema {
+        operation: "byte_patch",
+        input: &[
+            Field::new("old_len", Encoding::U32Be, Count::One, "length of the old string"),
+            Field::new("old", Encoding::Bytes, Count::Product(&["old_len"]), "the string the diff was made from"),
+            Field::new("ops", Encoding::Bytes, Count::Remaining, "byte_diff output"),
+        ],
+        output: &[Field::new("new", Encoding::Bytes, Count::Remaining, "the patched string")],
+    },
+    OperationSchema {
+        operation: "attest_reference",
+        input: REFERENCE,
+        output: REFERENCE,
+    },
+];
+
+/// Layout of a built-in operation, or `None` for an unknown name
+pub fn operation_schema(operation: &str) -> Option<OperationSchema> {
+    SCHEMAS
+        .iter()
+        .find(|schema| schema.operation == operation)
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BUILTIN_OPERATIONS;
+
+    #[test]
+    fn test_matrix_schema_reports_dimension_header() {
+        let schema = operation_schema("matrix_multiply").unwrap();
+        let names: Vec<&str> = schema.input.iter().map(|field| field.name).collect();
+        assert_eq!(names[..2], ["rows", "cols"]);
+
+        let rows = schema.input_field("rows").unwrap();
+        assert_eq!((rows.encoding, rows.size), (Encoding::U32Be, Some(4)));
+        assert_eq!(
+            schema.input_field("cells").unwrap().count,
+            Count::Product(&["rows", "cols"])
+        );
+
+        for operation in BUILTIN_OPERATIONS {
+            assert!(
+                operation_schema(operation).is_some(),
+                "no schema for {}",
+                operation
+            );
+        }
+        assert!(operation_schema("unknown").is_none());
+    }
+}
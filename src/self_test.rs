@@ -0,0 +1,125 @@
+//! Reproducibility self-test against embedded golden outputs
+//!
+//! A fixed battery of operations runs under a fixed seed and the SHA-256 of
+//! each output is compared with the value recorded on the reference platform.
+//! Any drift, such as a float operation rounding differently, is reported by
+//! operation name. `encrypt` and `sign` are covered by the crypto module's
+//! own tests and are not part of the battery.
+
+use sha2::{Digest, Sha256};
+
+use crate::{ephemeral_signing_key, DeterministicEngine};
+
+/// Seed used for every self-test computation
+pub const SELF_TEST_SEED: u64 = 0x5EED_7E57;
+
+/// Outcome of one self-test case
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfTestCase {
+    pub operation: &'static str,
+    pub passed: bool,
+}
+
+/// Pass/fail report for the whole battery
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SelfTestReport {
+    pub cases: Vec<SelfTestCase>,
+}
+
+impl SelfTestReport {
+    /// True when every output matched its golden hash
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|case| case.passed)
+    }
+
+    /// Operations whose output drifted or failed to compute
+    pub fn drifted(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.cases
+            .iter()
+            .filter(|case| !case.passed)
+            .map(|case| case.operation)
+    }
+}
+
+/// Run the battery and compare each output with its golden SHA-256
+pub fn self_test() -> SelfTestReport {
+    let mut engine = DeterministicEngine::new(Some(SELF_TEST_SEED), &ephemeral_signing_key());
+    let cases = battery()
+        .into_iter()
+        .map(|(operation, input, golden)| {
+            let passed = engine
+                .compute(operation, &input)
+                .map(|result| hex::encode(Sha256::digest(&result.value)) == golden)
+                .unwrap_or(false);
+            SelfTestCase { operation, passed }
+        })
+        .collect();
+    SelfTestReport { cases }
+}
+
+/// Fixed inputs paired with the SHA-256 of their expected output
+fn battery() -> Vec<(&'static str, Vec<u8>, &'static str)> {
+    let mut matrix = Vec::new();
+    matrix.extend_from_slice(&2u32.to_be_bytes());
+    matrix.extend_from_slice(&3u32.to_be_bytes());
+    for value in [1.5f64, -2.25, 3.0, 0.1, 1e-3, 42.0] {
+        matrix.extend_from_slice(&value.to_be_bytes());
+    }
+
+    let mut polynomial = Vec::new();
+    polynomial.extend_from_slice(&4u32.to_be_bytes());
+    polynomial.extend_from_slice(&1.75f64.to_be_bytes());
+    polynomial.extend_from_slice(b"self-test coefficients");
+
+    let mut sort = Vec::new();
+    sort.extend_from_slice(&4u32.to_be_bytes());
+    sort.extend_from_slice(&1u32.to_be_bytes());
+    for (key, tag) in [(9u64, b'a'), (2, b'b'), (9, b'c'), (0, b'd')] {
+        sort.extend_from_slice(&key.to_be_bytes());
+        sort.push(tag);
+    }
+
+    vec![
+        (
+            "hash",
+            b"deterministic self-test".to_vec(),
+            "3b3a3f83f2f96fff33f7f6ec0ec449125c5f480249584a3840861c48d24d4755",
+        ),
+        (
+            "matrix_multiply",
+            matrix,
+            "c4d4f55d524dbc7b1bf4dc4960203c4696ef08631e2c72233103d21fe6e30c47",
+        ),
+        (
+            "polynomial_eval",
+            polynomial,
+            "722a93b82701163b1121ebb6f6ac5363c9496aee06461992a40936764ad03535",
+        ),
+        (
+            "fibonacci",
+            vec![90],
+            "1b68b2688e919f2a82a48966ab12b620f889766213b9b6fec22ada65b687d48e",
+        ),
+        (
+            "sort",
+            sort,
+            "8c8954cbcc3753a996829aa3cf4f565d5370b93f9cf4d6e22fe53993e54f2ce7",
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes_on_reference_platform() {
+        let report = self_test();
+        assert_eq!(report.cases.len(), 5);
+        assert!(
+            report.passed(),
+            "drifted: {:?}",
+            report.drifted().collect::<Vec<_>>()
+        );
+    }
+}
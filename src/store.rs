@@ -0,0 +1,108 @@
+//! Content-addressed on-disk store for computation results
+//!
+//! Results are keyed by their `proof_hash`, so identical computations share a
+//! single file and any process pointing at the same directory can fetch them.
+//! This is independent of the engine's in-memory, input-keyed cache.
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use crate::result::ComputationResult;
+
+/// Directory-backed store mapping proof hashes to serialized results
+#[derive(Debug, Clone)]
+pub struct ResultStore {
+    root: PathBuf,
+}
+
+impl ResultStore {
+    /// Opens a store rooted at `root`, creating the directory if needed
+    pub fn open<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+        fs::create_dir_all(root.as_ref())?;
+        Ok(Self {
+            root: root.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Stores a result under its proof hash; an existing entry is left untouched
+    pub fn put(&self, result: &ComputationResult) -> io::Result<()> {
+        let path = self.entry_path(&result.proof_hash)?;
+        if path.exists() {
+            return Ok(());
+        }
+
+        // Write to a temporary file first so readers never observe a partial entry
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, serde_json::to_vec(result)?)?;
+        fs::rename(&temp_path, &path)
+    }
+
+    /// Fetches the result stored under `proof_hash`, if any
+    pub fn get(&self, proof_hash: &str) -> io::Result<Option<ComputationResult>> {
+        let path = self.entry_path(proof_hash)?;
+        match fs::read(&path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Checks whether a result is stored under `proof_hash`
+    pub fn contains(&self, proof_hash: &str) -> io::Result<bool> {
+        Ok(self.entry_path(proof_hash)?.exists())
+    }
+
+    /// Maps a proof hash to its file, rejecting anything that is not a hex digest
+    fn entry_path(&self, proof_hash: &str) -> io::Result<PathBuf> {
+        if proof_hash.is_empty() || !proof_hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid proof hash: {}", proof_hash),
+            ));
+        }
+        Ok(self
+            .root
+            .join(format!("{}.json", proof_hash.to_ascii_lowercase())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::{proof_v2, DEFAULT_PROOF_DOMAIN_TAG};
+    use crate::verifier::attestation::RootSigningKey;
+
+    #[test]
+    fn test_put_and_get_by_proof_hash() {
+        let dir = std::env::temp_dir().join(format!("result-store-{}", uuid::Uuid::new_v4()));
+        let store = ResultStore::open(&dir).unwrap();
+
+        let proof_hash = proof_v2(
+            DEFAULT_PROOF_DOMAIN_TAG,
+            "echo",
+            b"store me",
+            b"store me",
+            42,
+            &[],
+        );
+        let result = ComputationResult {
+            value: b"store me".to_vec(),
+            verification_signature: RootSigningKey::from_seed(&[7; 32])
+                .sign_result(proof_hash.as_bytes()),
+            proof_hash,
+            timestamp: 1_700_000_000,
+            seed: 42,
+            execution_time_ns: 0,
+            cpu_time_ns: None,
+        };
+
+        assert!(!store.contains(&result.proof_hash).unwrap());
+        store.put(&result).unwrap();
+        assert!(store.contains(&result.proof_hash).unwrap());
+        assert_eq!(store.get(&result.proof_hash).unwrap(), Some(result));
+        assert!(store.get("../escape").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+//! Ed25519 attestations over Merkle roots and computation results
+//!
+//! A signed root lets downstream consumers check where a lockfile tree came
+//! from using only the signer's public key. The signed message is the root
+//! prefixed with a domain tag, so a root signature cannot be mistaken for a
+//! signature over any other 32-byte value. Result signatures cover a proof
+//! hash under their own domain tag.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use ring::signature::{self, Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Domain-separation prefix for signed Merkle roots
+const ROOT_SIGNATURE_DOMAIN: &[u8] = b"MERKLE_ROOT_SIGNATURE_V1";
+
+/// Domain-separation prefix for signed computation results
+const RESULT_SIGNATURE_DOMAIN: &[u8] = b"COMPUTATION_RESULT_SIGNATURE_V1";
+
+/// Length in bytes of an Ed25519 signature
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Ed25519 signature, serialized as lowercase hex
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature(pub [u8; SIGNATURE_LEN]);
+
+impl Signature {
+    /// Decodes exactly `SIGNATURE_LEN` bytes of hex, in either case
+    pub fn from_hex(hex: &str) -> Result<Self, hex::FromHexError> {
+        let mut bytes = [0u8; SIGNATURE_LEN];
+        hex::decode_to_slice(hex, &mut bytes)?;
+        Ok(Self(bytes))
+    }
+
+    /// Lowercase hex encoding, as stored in serialized results
+    pub fn to_hex(self) -> String {
+        hex::encode(self.0)
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Self::from_hex(&hex).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Ed25519 key used to sign Merkle roots and computation results
+pub struct RootSigningKey {
+    key_pair: Ed25519KeyPair,
+}
+
+impl RootSigningKey {
+    /// Derives the key pair from a 32-byte secret seed
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        // Any 32 bytes form a valid Ed25519 seed, so this cannot be rejected
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(seed).expect("32-byte Ed25519 seed");
+        Self { key_pair }
+    }
+
+    /// The 32-byte public key consumers verify against
+    pub fn public_key(&self) -> &[u8] {
+        self.key_pair.public_key().as_ref()
+    }
+
+    /// Signs a root hash
+    pub fn sign(&self, root: &[u8]) -> Signature {
+        self.sign_in_domain(ROOT_SIGNATURE_DOMAIN, root)
+    }
+
+    /// Signs a computation result's proof hash
+    pub fn sign_result(&self, proof_hash: &[u8]) -> Signature {
+        self.sign_in_domain(RESULT_SIGNATURE_DOMAIN, proof_hash)
+    }
+
+    /// Signs `message` prefixed with `domain`, for other statements made by this key
+    pub(super) fn sign_in_domain(&self, domain: &[u8], message: &[u8]) -> Signature {
+        let mut bytes = [0u8; SIGNATURE_LEN];
+        bytes.copy_from_slice(
+            self.key_pair
+                .sign(&signed_message(domain, message))
+                .as_ref(),
+        );
+        Signature(bytes)
+    }
+}
+
+/// Checks `signature` over `root` against an Ed25519 public key
+pub fn verify_signed_root(root: &[u8], signature: &Signature, public_key: &[u8]) -> bool {
+    verify_in_domain(ROOT_SIGNATURE_DOMAIN, root, signature, public_key)
+}
+
+/// Checks `signature` over a result's `proof_hash` against an Ed25519 public key
+pub fn verify_signed_result(proof_hash: &[u8], signature: &Signature, public_key: &[u8]) -> bool {
+    verify_in_domain(RESULT_SIGNATURE_DOMAIN, proof_hash, signature, public_key)
+}
+
+/// Checks a signature made by `RootSigningKey::sign_in_domain`
+pub(super) fn verify_in_domain(
+    domain: &[u8],
+    message: &[u8],
+    signature: &Signature,
+    public_key: &[u8],
+) -> bool {
+    signature::UnparsedPublicKey::new(&signature::ED25519, public_key)
+        .verify(&signed_message(domain, message), &signature.0)
+        .is_ok()
+}
+
+fn signed_message(domain: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(domain.len() + body.len());
+    message.extend_from_slice(domain);
+    message.extend_from_slice(body);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verifier::merkle::{Dependency, MerkleTree};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn signed_tree() -> (MerkleTree, RootSigningKey) {
+        let mut tree = MerkleTree::new();
+        tree.build_from_dependencies(vec![
+            Dependency {
+                name: "left-pad".to_string(),
+                version: "1.3.0".to_string(),
+                integrity: None,
+                resolved: None,
+                marker: None,
+            },
+            Dependency {
+                name: "semver".to_string(),
+                version: "6.3.1".to_string(),
+                integrity: None,
+                resolved: None,
+                marker: None,
+            },
+        ])
+        .unwrap();
+        (tree, RootSigningKey::from_seed(&[7u8; 32]))
+    }
+
+    #[test]
+    fn test_signed_root_verifies_with_public_key() {
+        let (tree, key) = signed_tree();
+        let signature = tree.sign_root(&key).unwrap();
+        let root = tree.root_hash().unwrap();
+        assert!(verify_signed_root(&root, &signature, key.public_key()));
+
+        let other_key = RootSigningKey::from_seed(&[8u8; 32]);
+        assert!(!verify_signed_root(
+            &root,
+            &signature,
+            other_key.public_key()
+        ));
+        assert!(MerkleTree::new().sign_root(&key).is_none());
+    }
+
+    #[test]
+    fn test_tampered_root_fails_verification() {
+        let (tree, key) = signed_tree();
+        let signature = tree.sign_root(&key).unwrap();
+        let mut root = tree.root_hash().unwrap();
+        root[0] ^= 0x01;
+        assert!(!verify_signed_root(&root, &signature, key.public_key()));
+    }
+
+    #[test]
+    fn test_result_signatures_are_separate_from_root_signatures() {
+        let key = RootSigningKey::from_seed(&[7u8; 32]);
+        let proof_hash = b"3f1a";
+        let signature = key.sign_result(proof_hash);
+        assert!(verify_signed_result(
+            proof_hash,
+            &signature,
+            key.public_key()
+        ));
+        assert!(!verify_signed_result(b"3f1b", &signature, key.public_key()));
+        assert!(!verify_signed_root(
+            proof_hash,
+            &signature,
+            key.public_key()
+        ));
+    }
+
+    #[test]
+    fn test_signature_round_trips_through_json_as_hex() {
+        let mut bytes = [0u8; SIGNATURE_LEN];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = (i * 7) as u8;
+        }
+        let signature = Signature(bytes);
+
+        let json = serde_json::to_string(&signature).unwrap();
+        assert_eq!(json, alloc::format!("\"{}\"", hex::encode(bytes)));
+        let decoded: Signature = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.0, bytes);
+
+        assert_eq!(
+            Signature::from_hex(&signature.to_hex().to_uppercase()),
+            Ok(signature)
+        );
+        assert!(serde_json::from_str::<Signature>("\"abcd\"").is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Signed attestation bundles for release pipelines
+//!
+//! A bundle publishes a lockfile's Merkle root together with the package
+//! manager, dependency count and a timestamp. It carries two signatures from
+//! the same key: the plain root signature, so consumers can check inclusion
+//! proofs with `verify_signed_root` as for any signed tree, and a signature
+//! over the whole statement, so none of the metadata can be edited without
+//! invalidating the bundle.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::attestation::{
+    verify_in_domain, verify_signed_root, RootSigningKey, Signature, SIGNATURE_LEN,
+};
+use super::lockfile::{LockfileParser, ParseError};
+use super::merkle::MerkleTree;
+
+/// Domain-separation prefix for signed bundle statements
+const BUNDLE_SIGNATURE_DOMAIN: &[u8] = b"LOCKFILE_ATTESTATION_V1";
+
+/// Length in bytes of a Merkle root
+const ROOT_LEN: usize = 32;
+
+/// Errors raised while building or checking an attestation bundle
+#[derive(Debug, Error)]
+pub enum BundleError {
+    /// The lockfile could not be parsed
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+
+    /// A bundle field does not decode to the expected shape
+    #[error("malformed bundle: {0}")]
+    Malformed(&'static str),
+
+    /// A signature does not match the bundle contents and public key
+    #[error("bundle signature does not verify")]
+    BadSignature,
+}
+
+/// Published attestation for one lockfile
+///
+/// Binary fields are lowercase hex so the bundle reads naturally as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttestationBundle {
+    pub package_manager: String,
+    pub dependency_count: u64,
+    /// Seconds since the Unix epoch, as supplied by the builder
+    pub timestamp: u64,
+    pub root: String,
+    /// Signature over the root alone, checkable with `verify_signed_root`
+    pub root_signature: String,
+    /// Signature over every other field
+    pub signature: String,
+}
+
+/// Parses the lockfile at `path` and signs an attestation of its tree
+///
+/// The tree is built as `verify_lockfile` builds it, so an empty lockfile
+/// attests to the empty root. The timestamp is taken as an argument to keep
+/// bundles reproducible.
+pub fn build_attestation(
+    parser: &impl LockfileParser,
+    path: impl AsRef<Path>,
+    key: &RootSigningKey,
+    timestamp: u64,
+) -> Result<AttestationBundle, BundleError> {
+    let dependencies = parser.parse_lockfile(path)?;
+    let dependency_count = dependencies.len() as u64;
+
+    let mut tree = MerkleTree::new().with_empty_root();
+    tree.build_from_dependencies(dependencies)
+        .expect("an empty-root tree builds from any dependency list");
+    let root = tree
+        .root_hash()
+        .expect("an empty-root tree always has a root");
+    let package_manager = parser.package_manager().to_string();
+
+    let statement = statement(&root, &package_manager, dependency_count, timestamp);
+    Ok(AttestationBundle {
+        root_signature: hex::encode(key.sign(&root).0),
+        signature: hex::encode(key.sign_in_domain(BUNDLE_SIGNATURE_DOMAIN, &statement).0),
+        root: hex::encode(root),
+        package_manager,
+        dependency_count,
+        timestamp,
+    })
+}
+
+/// Checks both bundle signatures against `public_key`
+pub fn verify_attestation(
+    bundle: &AttestationBundle,
+    public_key: &[u8],
+) -> Result<(), BundleError> {
+    let root = hex::decode(&bundle.root).map_err(|_| BundleError::Malformed("root is not hex"))?;
+    if root.len() != ROOT_LEN {
+        return Err(BundleError::Malformed("root is not 32 bytes"));
+    }
+    let root_signature = decode_signature(&bundle.root_signature)?;
+    let signature = decode_signature(&bundle.signature)?;
+
+    let statement = statement(
+        &root,
+        &bundle.package_manager,
+        bundle.dependency_count,
+        bundle.timestamp,
+    );
+    if verify_signed_root(&root, &root_signature, public_key)
+        && verify_in_domain(BUNDLE_SIGNATURE_DOMAIN, &statement, &signature, public_key)
+    {
+        Ok(())
+    } else {
+        Err(BundleError::BadSignature)
+    }
+}
+
+/// Length-prefixed encoding of the signed fields, so no two bundles share one
+fn statement(root: &[u8], package_manager: &str, dependency_count: u64, timestamp: u64) -> Vec<u8> {
+    let mut message = Vec::new();
+    for field in [root, package_manager.as_bytes()] {
+        message.extend_from_slice(&(field.len() as u64).to_be_bytes());
+        message.extend_from_slice(field);
+    }
+    message.extend_from_slice(&dependency_count.to_be_bytes());
+    message.extend_from_slice(&timestamp.to_be_bytes());
+    message
+}
+
+fn decode_signature(encoded: &str) -> Result<Signature, BundleError> {
+    let bytes = hex::decode(encoded).map_err(|_| BundleError::Malformed("signature is not hex"))?;
+    let bytes: [u8; SIGNATURE_LEN] = bytes
+        .try_into()
+        .map_err(|_| BundleError::Malformed("signature is not 64 bytes"))?;
+    Ok(Signature(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verifier::lockfile::{verify_lockfile, NpmLockfileParser};
+    use std::fs::File;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_bundle_round_trips_and_rejects_tampering() {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/package-lock-v3.json");
+        let key = RootSigningKey::from_seed(&[7u8; 32]);
+        let bundle = build_attestation(&NpmLockfileParser, &fixture, &key, 1_700_000_000).unwrap();
+        assert_eq!(bundle.package_manager, "npm");
+        assert_eq!(bundle.dependency_count, 4);
+
+        // Publish and read back as the release pipeline would
+        let json = serde_json::to_string(&bundle).unwrap();
+        let published: AttestationBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(published, bundle);
+        verify_attestation(&published, key.public_key()).unwrap();
+
+        // The attested root is the one lockfile verification recomputes
+        let root = hex::decode(&published.root).unwrap();
+        let reader = BufReader::new(File::open(&fixture).unwrap());
+        assert!(verify_lockfile(&NpmLockfileParser, reader, &root, None).unwrap());
+
+        let other_key = RootSigningKey::from_seed(&[8u8; 32]);
+        assert!(matches!(
+            verify_attestation(&bundle, other_key.public_key()),
+            Err(BundleError::BadSignature)
+        ));
+
+        let recounted = AttestationBundle {
+            dependency_count: 3,
+            ..bundle.clone()
+        };
+        assert!(matches!(
+            verify_attestation(&recounted, key.public_key()),
+            Err(BundleError::BadSignature)
+        ));
+        let backdated = AttestationBundle {
+            timestamp: 1,
+            ..bundle.clone()
+        };
+        assert!(matches!(
+            verify_attestation(&backdated, key.public_key()),
+            Err(BundleError::BadSignature)
+        ));
+        let truncated = AttestationBundle {
+            signature: bundle.signature[..64].to_string(),
+            ..bundle
+        };
+        assert!(matches!(
+            verify_attestation(&truncated, key.public_key()),
+            Err(BundleError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,323 @@
+//! Verification-only engine for deployments that never compute
+//!
+//! `VerifierEngine` checks results and dependency inclusion but has no
+//! compute, encrypt or sign entry points, so a verifier deployment carries
+//! no operation code paths it does not need. Signed Merkle roots and result
+//! signatures are both checked against one Ed25519 public key, the public
+//! half of the secret the producing engine was built with.
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::result::{
+    proof_operation, proof_v2, ComputationResult, ReferenceAttestation, DEFAULT_PROOF_DOMAIN_TAG,
+    PROOF_HASH, PROOF_VERSION,
+};
+use crate::verifier::attestation::{
+    verify_signed_result, verify_signed_root, Signature as RootSignature,
+};
+use crate::verifier::merkle::Dependency;
+use crate::verifier::proof::ProofStep;
+use crate::verifier::pruned::PrunedTree;
+
+/// Everything a third party needs to check an engine's results
+///
+/// Carries the public half of the result signing key; the seed each result
+/// was produced under travels with the result itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationParams {
+    pub proof_version: u32,
+    /// Hash behind proof hashes, independent of the `"hash"` operation
+    pub proof_hash: String,
+    /// Hash function behind the `"hash"` operation, for information only
+    pub hash_algorithm: String,
+    /// Proof domain tag as lowercase hex
+    pub domain_tag: String,
+    /// Ed25519 public key results are signed under, as lowercase hex
+    pub public_key: String,
+    /// Length `"hash"` digests are truncated to, if any
+    #[serde(default)]
+    pub hash_truncation: Option<usize>,
+}
+
+/// Verify a context-free result using only an exported parameter bundle
+///
+/// Parameters for an unsupported proof scheme are an error rather than a
+/// failed verification.
+pub fn verify_with_params(
+    result: &ComputationResult,
+    operation: &str,
+    input: &[u8],
+    params: &VerificationParams,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    if params.proof_version != PROOF_VERSION || params.proof_hash != PROOF_HASH {
+        return Err(format!(
+            "Unsupported proof scheme: v{} over {}",
+            params.proof_version, params.proof_hash
+        )
+        .into());
+    }
+    let domain_tag = hex::decode(&params.domain_tag)?;
+    let operation = proof_operation(operation, params.hash_truncation);
+    let public_key = hex::decode(&params.public_key)?;
+    verify_v2(
+        &public_key,
+        &domain_tag,
+        result.seed,
+        result,
+        &operation,
+        input,
+        &[],
+    )
+}
+
+/// Re-derive a v2 proof and check it, the signature and the seed
+fn verify_v2(
+    public_key: &[u8],
+    domain_tag: &[u8],
+    seed: u64,
+    result: &ComputationResult,
+    operation: &str,
+    input: &[u8],
+    context: &[u8],
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let expected_proof = proof_v2(domain_tag, operation, input, &result.value, seed, context);
+    Ok(expected_proof == result.proof_hash
+        && verify_signed_result(
+            result.proof_hash.as_bytes(),
+            &result.verification_signature,
+            public_key,
+        )
+        && result.seed == seed)
+}
+
+/// Checks results and inclusion proofs without any way to produce them
+pub struct VerifierEngine {
+    seed: u64,
+    public_key: Vec<u8>,
+    domain_tag: Vec<u8>,
+    hash_truncation: Option<usize>,
+}
+
+impl VerifierEngine {
+    /// Verifier for roots and results signed by `public_key`, from an engine with `seed`
+    pub fn new(public_key: impl Into<Vec<u8>>, seed: u64) -> Self {
+        Self {
+            seed,
+            public_key: public_key.into(),
+            domain_tag: DEFAULT_PROOF_DOMAIN_TAG.to_vec(),
+            hash_truncation: None,
+        }
+    }
+
+    /// Use the domain tag the producing engine was configured with
+    pub fn with_domain_tag(mut self, tag: impl Into<Vec<u8>>) -> Self {
+        self.domain_tag = tag.into();
+        self
+    }
+
+    /// Match an engine built with `with_hash_truncation(len)`
+    pub fn with_hash_truncation(mut self, len: usize) -> Self {
+        self.hash_truncation = Some(len);
+        self
+    }
+
+    /// Verify a result produced without a context
+    pub fn verify_result(
+        &self,
+        result: &ComputationResult,
+        operation: &str,
+        input: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        self.verify_result_with_context(result, operation, input, &[])
+    }
+
+    /// Re-derive the proof and check it, the signature and the seed
+    pub fn verify_result_with_context(
+        &self,
+        result: &ComputationResult,
+        operation: &str,
+        input: &[u8],
+        context: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let operation = proof_operation(operation, self.hash_truncation);
+        verify_v2(
+            &self.public_key,
+            &self.domain_tag,
+            self.seed,
+            result,
+            &operation,
+            input,
+            context,
+        )
+    }
+
+    /// Verify an `attest_reference` result and that `content` is what it refers to
+    ///
+    /// The caller fetches `content` from the attested URI; only its SHA-256
+    /// digest is compared, so any copy of the content will do.
+    pub fn verify_reference(
+        &self,
+        result: &ComputationResult,
+        content: &[u8],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let reference = ReferenceAttestation::parse(&result.value)?;
+        Ok(
+            self.verify_result(result, "attest_reference", &result.value)?
+                && reference.matches(content),
+        )
+    }
+
+    /// Whether `root` carries a valid signature and `proof` connects `dependency` to it
+    ///
+    /// Leaves are hashed as `MerkleTree::new` hashes them.
+    pub fn verify_inclusion(
+        &self,
+        root: &[u8],
+        root_signature: &RootSignature,
+        dependency: &Dependency,
+        proof: Vec<ProofStep>,
+    ) -> bool {
+        verify_signed_root(root, root_signature, &self.public_key)
+            && PrunedTree::new(root.to_vec()).insert(dependency, proof)
+    }
+}
+
+/// Verify a batch of results in parallel, each signed under `public_key`
+///
+/// Entries are `(result, operation, input)` and the output holds one flag per
+/// entry, in input order. A result that fails to verify or raises an error is
+/// `false`. Each proof is re-derived under the seed the result carries; the
+/// signature check is what ties the result to the holder of `public_key`.
+pub fn verify_results_parallel(
+    results: &[(ComputationResult, String, Vec<u8>)],
+    public_key: &[u8],
+) -> Vec<bool> {
+    results
+        .par_iter()
+        .map(|(result, operation, input)| {
+            VerifierEngine::new(public_key, result.seed)
+                .verify_result(result, operation, input)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verifier::attestation::RootSigningKey;
+    use crate::verifier::merkle::tests::numbered_dependencies;
+    use crate::verifier::merkle::MerkleTree;
+    use sha2::{Digest, Sha256};
+
+    /// A `"hash"` result over `input`, proved under `seed` and signed by `key`
+    fn hash_result(key: &RootSigningKey, seed: u64, input: &[u8]) -> ComputationResult {
+        let value = Sha256::digest(input).to_vec();
+        let proof_hash = proof_v2(DEFAULT_PROOF_DOMAIN_TAG, "hash", input, &value, seed, &[]);
+        ComputationResult {
+            verification_signature: key.sign_result(proof_hash.as_bytes()),
+            value,
+            proof_hash,
+            timestamp: 0,
+            seed,
+            execution_time_ns: 0,
+            cpu_time_ns: None,
+        }
+    }
+
+    #[test]
+    fn test_verifier_engine_checks_results_and_signed_inclusion() {
+        let key = RootSigningKey::from_seed(&[7u8; 32]);
+        let result = hash_result(&key, 42, b"payload");
+
+        let verifier = VerifierEngine::new(key.public_key(), 42);
+        assert!(verifier.verify_result(&result, "hash", b"payload").unwrap());
+        assert!(!verifier.verify_result(&result, "hash", b"other").unwrap());
+        assert!(!VerifierEngine::new(key.public_key(), 43)
+            .verify_result(&result, "hash", b"payload")
+            .unwrap());
+
+        // A valid signature under any other key is rejected
+        let other_key = RootSigningKey::from_seed(&[8u8; 32]);
+        let foreign = hash_result(&other_key, 42, b"payload");
+        assert!(!verifier
+            .verify_result(&foreign, "hash", b"payload")
+            .unwrap());
+
+        let dependencies = numbered_dependencies(5);
+        let mut tree = MerkleTree::new();
+        tree.build_from_dependencies(dependencies.clone()).unwrap();
+        let root = tree.root_hash().unwrap();
+        let signature = tree.sign_root(&key).unwrap();
+        let proof = tree.generate_proof(&dependencies[3]).unwrap();
+
+        assert!(verifier.verify_inclusion(&root, &signature, &dependencies[3], proof.clone()));
+        assert!(!verifier.verify_inclusion(&root, &signature, &dependencies[2], proof.clone()));
+
+        let untrusted = tree.sign_root(&other_key).unwrap();
+        assert!(!verifier.verify_inclusion(&root, &untrusted, &dependencies[3], proof));
+    }
+
+    #[test]
+    fn test_params_check_public_key_and_scheme() {
+        let key = RootSigningKey::from_seed(&[7u8; 32]);
+        let result = hash_result(&key, 42, b"payload");
+        let params = VerificationParams {
+            proof_version: PROOF_VERSION,
+            proof_hash: PROOF_HASH.to_string(),
+            hash_algorithm: "sha256".to_string(),
+            domain_tag: hex::encode(DEFAULT_PROOF_DOMAIN_TAG),
+            public_key: hex::encode(key.public_key()),
+            hash_truncation: None,
+        };
+        assert!(verify_with_params(&result, "hash", b"payload", &params).unwrap());
+        assert!(!verify_with_params(&result, "hash", b"other", &params).unwrap());
+
+        let other_key = VerificationParams {
+            public_key: hex::encode(RootSigningKey::from_seed(&[8u8; 32]).public_key()),
+            ..params.clone()
+        };
+        assert!(!verify_with_params(&result, "hash", b"payload", &other_key).unwrap());
+        let other_tag = VerificationParams {
+            domain_tag: hex::encode("deployment-a"),
+            ..params.clone()
+        };
+        assert!(!verify_with_params(&result, "hash", b"payload", &other_tag).unwrap());
+        let future = VerificationParams {
+            proof_version: 3,
+            ..params
+        };
+        assert!(verify_with_params(&result, "hash", b"payload", &future).is_err());
+    }
+
+    #[test]
+    fn test_parallel_batch_flags_tampered_results_in_order() {
+        let key = RootSigningKey::from_seed(&[7u8; 32]);
+        let mut batch = Vec::new();
+        for (seed, input) in [
+            (1u64, b"alpha"),
+            (2, b"bravo"),
+            (3, b"delta"),
+            (4, b"gamma"),
+        ] {
+            batch.push((
+                hash_result(&key, seed, input),
+                "hash".to_string(),
+                input.to_vec(),
+            ));
+        }
+        batch[1].0.value[0] ^= 1;
+        batch[3].2 = b"omega".to_vec();
+
+        // Internally consistent, but signed under a different key
+        let forged = hash_result(&RootSigningKey::from_seed(&[5u8; 32]), 5, b"kappa");
+        batch.push((forged, "hash".to_string(), b"kappa".to_vec()));
+
+        assert_eq!(
+            verify_results_parallel(&batch, key.public_key()),
+            vec![true, false, true, false, false]
+        );
+        assert!(verify_results_parallel(&[], key.public_key()).is_empty());
+    }
+}
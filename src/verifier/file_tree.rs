@@ -0,0 +1,177 @@
+//! Merkle roots over directory trees for reproducible-build attestation
+//!
+//! Each regular file and symlink under the root becomes one `Dependency`
+//! leaf, and leaves are ordered by path, so the root commits to file names,
+//! layout, contents and modes. It does not depend on the order in which the
+//! file system lists directories.
+//!
+//! - `name` holds the path relative to the root, with `/` separators on
+//!   every platform.
+//! - `version` holds a git-style mode: `100644` for regular files, `100755`
+//!   for executables (Unix only) and `120000` for symlinks.
+//! - `integrity` holds the hex SHA-256 of a regular file's contents.
+//! - `resolved` holds a symlink's target, recorded verbatim.
+//!
+//! Symlinks are never followed, so cycles and links that point outside the
+//! root cannot change the result. Directories contribute only through the
+//! paths of the files they contain, which means empty directories do not
+//! affect the root. Any other file type, or a path that is not valid
+//! UTF-8, is an error. Call `MerkleTree::with_empty_root` to attest an
+//! empty directory.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::merkle::{Dependency, MerkleTree};
+
+const MODE_FILE: &str = "100644";
+const MODE_EXECUTABLE: &str = "100755";
+const MODE_SYMLINK: &str = "120000";
+
+/// Errors raised while walking a directory tree
+#[derive(Debug, Error)]
+pub enum FileTreeError {
+    /// A directory or file could not be read
+    #[error("failed to read file tree: {0}")]
+    Io(#[from] io::Error),
+
+    /// A path below the root is not valid UTF-8
+    #[error("path is not valid UTF-8: {0:?}")]
+    NonUtf8Path(PathBuf),
+
+    /// An entry is neither a regular file, a directory nor a symlink
+    #[error("unsupported file type: {0:?}")]
+    UnsupportedFileType(PathBuf),
+
+    /// The directory holds no files and the tree has no empty root
+    #[error("no files under {0:?}")]
+    Empty(PathBuf),
+}
+
+/// Collects one leaf per file or symlink below `root`, sorted by path
+pub fn file_tree_dependencies(root: &Path) -> Result<Vec<Dependency>, FileTreeError> {
+    let mut dependencies = Vec::new();
+    walk(root, "", &mut dependencies)?;
+    dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(dependencies)
+}
+
+/// Builds a tree whose root attests the directory at `root`
+///
+/// `tree` supplies the leaf fields, hasher and empty-root setting, which
+/// apply exactly as they do for lockfile dependencies.
+pub fn build_file_tree(root: &Path, mut tree: MerkleTree) -> Result<MerkleTree, FileTreeError> {
+    let dependencies = file_tree_dependencies(root)?;
+    // Building only fails for no leaves on a tree without an empty root
+    tree.build_from_dependencies(dependencies)
+        .map_err(|_| FileTreeError::Empty(root.to_path_buf()))?;
+    Ok(tree)
+}
+
+fn walk(dir: &Path, prefix: &str, dependencies: &mut Vec<Dependency>) -> Result<(), FileTreeError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| FileTreeError::NonUtf8Path(path.clone()))?;
+        let relative = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+
+        // `file_type` does not traverse symlinks
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            walk(&path, &relative, dependencies)?;
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(&path)?;
+            let target = target
+                .to_str()
+                .ok_or_else(|| FileTreeError::NonUtf8Path(path.clone()))?
+                .to_string();
+            dependencies.push(Dependency {
+                name: relative,
+                version: MODE_SYMLINK.to_string(),
+                integrity: None,
+                resolved: Some(target),
+                marker: None,
+            });
+        } else if file_type.is_file() {
+            let mut hasher = Sha256::new();
+            io::copy(&mut File::open(&path)?, &mut hasher)?;
+            dependencies.push(Dependency {
+                name: relative,
+                version: file_mode(&entry.metadata()?).to_string(),
+                integrity: Some(hex::encode(hasher.finalize())),
+                resolved: None,
+                marker: None,
+            });
+        } else {
+            return Err(FileTreeError::UnsupportedFileType(path));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> &'static str {
+    use std::os::unix::fs::PermissionsExt;
+
+    if metadata.permissions().mode() & 0o111 != 0 {
+        MODE_EXECUTABLE
+    } else {
+        MODE_FILE
+    }
+}
+
+#[cfg(not(unix))]
+fn file_mode(_metadata: &fs::Metadata) -> &'static str {
+    MODE_FILE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_tree_root_is_stable_and_content_sensitive() {
+        let dir = std::env::temp_dir().join(format!("file-tree-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("src/bin")).unwrap();
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        fs::write(dir.join("src/lib.rs"), "pub fn demo() {}\n").unwrap();
+        fs::write(dir.join("src/bin/main.rs"), "fn main() {}\n").unwrap();
+
+        let root = |dir: &Path| {
+            build_file_tree(dir, MerkleTree::new())
+                .unwrap()
+                .root_hash_hex()
+                .unwrap()
+        };
+        let names: Vec<String> = file_tree_dependencies(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        assert_eq!(names, ["Cargo.toml", "src/bin/main.rs", "src/lib.rs"]);
+
+        let original = root(&dir);
+        assert_eq!(
+            original,
+            "0ae6ea26b5c681e9cfdb1c80c5e580cce35d23f6bcec02debe8ef347fff9a084"
+        );
+        assert_eq!(root(&dir), original);
+
+        fs::write(dir.join("src/lib.rs"), "pub fn demo() { }\n").unwrap();
+        assert_ne!(root(&dir), original);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
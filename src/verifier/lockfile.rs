@@ -0,0 +1,489 @@
+//! Lockfile parsers feeding dependencies into the Merkle tree
+//!
+//! Parsing reads from files and `BufRead` sources, so this module is only
+//! built with the `std` feature.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use super::merkle::{Dependency, MerkleTree};
+
+/// Highest npm `lockfileVersion` the parser understands
+const MAX_NPM_LOCKFILE_VERSION: u64 = 3;
+
+/// Errors raised while reading or interpreting a lockfile
+#[derive(Debug, Error)]
+pub enum ParseError {
+    /// The lockfile could not be read
+    #[error("failed to read lockfile: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The content is malformed; `line` is 1-based
+    #[error("syntax error on line {line}: {msg}")]
+    Syntax { line: usize, msg: String },
+
+    /// The lockfile declares a format version this parser does not handle
+    #[error("unsupported lockfile version {0}")]
+    UnsupportedVersion(u64),
+}
+
+/// Trait for parsing lockfiles from different package managers
+pub trait LockfileParser {
+    /// Parses lockfile content, handing each dependency to `visit` in order
+    ///
+    /// The parser itself collects no dependencies. How much of the input it
+    /// holds while visiting depends on the format; see each implementation.
+    fn parse_each(
+        &self,
+        reader: impl BufRead,
+        visit: &mut dyn FnMut(Dependency),
+    ) -> Result<(), ParseError>;
+
+    /// Parses lockfile content from any buffered reader
+    fn parse_reader(&self, reader: impl BufRead) -> Result<Vec<Dependency>, ParseError> {
+        let mut dependencies = Vec::new();
+        self.parse_each(reader, &mut |dependency| dependencies.push(dependency))?;
+        Ok(dependencies)
+    }
+
+    /// Parses a lockfile and extracts dependency information
+    fn parse_lockfile<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Dependency>, ParseError> {
+        let file = File::open(path)?;
+        self.parse_reader(BufReader::new(file))
+    }
+
+    /// Returns the package manager type
+    fn package_manager(&self) -> &'static str;
+}
+
+/// npm lockfile parser implementation
+pub struct NpmLockfileParser;
+
+impl NpmLockfileParser {
+    /// Visits entries of a v1 `dependencies` object, descending into nested ones
+    fn collect_dependencies(
+        entries: &serde_json::Map<String, serde_json::Value>,
+        visit: &mut dyn FnMut(Dependency),
+    ) -> Result<(), ParseError> {
+        for (name, entry) in entries {
+            let version = entry
+                .get("version")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| missing_version(format!("dependency '{}' has no version", name)))?;
+
+            visit(Dependency {
+                name: name.clone(),
+                version: version.to_string(),
+                integrity: entry
+                    .get("integrity")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                resolved: entry
+                    .get("resolved")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                marker: None,
+            });
+
+            if let Some(nested) = entry.get("dependencies").and_then(|v| v.as_object()) {
+                Self::collect_dependencies(nested, visit)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Visits entries of a v2+ `packages` object keyed by install path
+    ///
+    /// The package name is the path segment after the last `node_modules/`,
+    /// which keeps scopes intact (`node_modules/a/node_modules/@s/b` is `@s/b`).
+    /// The root project (empty key) and workspace links carry no installable
+    /// version and are skipped.
+    fn collect_packages(
+        entries: &serde_json::Map<String, serde_json::Value>,
+        visit: &mut dyn FnMut(Dependency),
+    ) -> Result<(), ParseError> {
+        for (path, entry) in entries {
+            if path.is_empty() || entry.get("link").and_then(|v| v.as_bool()) == Some(true) {
+                continue;
+            }
+
+            let name = match path.rsplit_once("node_modules/") {
+                Some((_, name)) => name.to_string(),
+                None => entry
+                    .get("name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(path)
+                    .to_string(),
+            };
+            let version = entry
+                .get("version")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| missing_version(format!("package '{}' has no version", path)))?;
+
+            visit(Dependency {
+                name,
+                version: version.to_string(),
+                integrity: entry
+                    .get("integrity")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                resolved: entry
+                    .get("resolved")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                marker: None,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl LockfileParser for NpmLockfileParser {
+    /// The whole JSON document is read into memory before the first visit,
+    /// since `lockfileVersion` may follow the entries it decides between. Memory
+    /// use therefore grows with the lockfile, not just with one entry.
+    fn parse_each(
+        &self,
+        reader: impl BufRead,
+        visit: &mut dyn FnMut(Dependency),
+    ) -> Result<(), ParseError> {
+        let lockfile: serde_json::Value = serde_json::from_reader(reader).map_err(|e| {
+            if e.is_io() {
+                ParseError::Io(e.into())
+            } else {
+                ParseError::Syntax {
+                    line: e.line(),
+                    msg: e.to_string(),
+                }
+            }
+        })?;
+        // v2 lockfiles carry both layouts and v3 only `packages`; prefer it when present
+        let lockfile_version = lockfile
+            .get("lockfileVersion")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1);
+        if lockfile_version == 0 || lockfile_version > MAX_NPM_LOCKFILE_VERSION {
+            return Err(ParseError::UnsupportedVersion(lockfile_version));
+        }
+        let packages = lockfile.get("packages").and_then(|v| v.as_object());
+
+        match packages {
+            Some(entries) if lockfile_version >= 2 => Self::collect_packages(entries, visit)?,
+            _ => {
+                if let Some(entries) = lockfile.get("dependencies").and_then(|v| v.as_object()) {
+                    Self::collect_dependencies(entries, visit)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn package_manager(&self) -> &'static str {
+        "npm"
+    }
+}
+
+/// pip requirements.txt parser implementation
+///
+/// Extras (`name[extra]==1.0`) are dropped from the name, and an environment
+/// marker after `;` is kept in `Dependency::marker`.
+pub struct PipLockfileParser;
+
+impl LockfileParser for PipLockfileParser {
+    /// Reads one line at a time, so only the current requirement is held
+    fn parse_each(
+        &self,
+        reader: impl BufRead,
+        visit: &mut dyn FnMut(Dependency),
+    ) -> Result<(), ParseError> {
+        for (index, line) in reader.lines().enumerate() {
+            // Non-UTF-8 bytes are malformed content, not a failure to read
+            let line = line.map_err(|e| match e.kind() {
+                ErrorKind::InvalidData => ParseError::Syntax {
+                    line: index + 1,
+                    msg: e.to_string(),
+                },
+                _ => ParseError::Io(e),
+            })?;
+            // Drop inline comments, then skip blanks and pip options such as `-r`
+            let requirement = line.split(" #").next().unwrap_or("").trim();
+            if requirement.is_empty()
+                || requirement.starts_with('#')
+                || requirement.starts_with('-')
+            {
+                continue;
+            }
+
+            let (requirement, marker) = match requirement.split_once(';') {
+                Some((requirement, marker)) => (
+                    requirement.trim(),
+                    Some(marker.trim()).filter(|m| !m.is_empty()),
+                ),
+                None => (requirement, None),
+            };
+            let (name, version) =
+                requirement
+                    .split_once("==")
+                    .ok_or_else(|| ParseError::Syntax {
+                        line: index + 1,
+                        msg: format!("unpinned requirement: {}", requirement),
+                    })?;
+
+            let name = name.split('[').next().unwrap_or(name);
+
+            visit(Dependency {
+                name: name.trim().to_string(),
+                version: version.trim().to_string(),
+                integrity: None,
+                resolved: None,
+                marker: marker.map(str::to_string),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn package_manager(&self) -> &'static str {
+        "pip"
+    }
+}
+
+/// Callback invoked every `interval` dependencies during `verify_lockfile`
+///
+/// The callback receives the number of dependencies processed so far and the
+/// time elapsed since verification started.
+pub struct ProgressReporter<'a> {
+    interval: usize,
+    callback: &'a mut dyn FnMut(usize, Duration),
+}
+
+impl<'a> ProgressReporter<'a> {
+    /// Reports after every `interval` dependencies; an interval of 0 is treated as 1
+    pub fn new(interval: usize, callback: &'a mut dyn FnMut(usize, Duration)) -> Self {
+        Self {
+            interval: interval.max(1),
+            callback,
+        }
+    }
+}
+
+/// Parses a lockfile and checks its dependencies hash to `expected_root`
+///
+/// Leaves use the default `MerkleTree` settings, with an empty lockfile
+/// hashing to the empty root. `progress`, when given, is called as
+/// dependencies are read, so long verifications can report how far they are.
+/// Every dependency is kept until the end, as the tree is built from the
+/// complete list.
+pub fn verify_lockfile(
+    parser: &impl LockfileParser,
+    reader: impl BufRead,
+    expected_root: &[u8],
+    mut progress: Option<ProgressReporter<'_>>,
+) -> Result<bool, ParseError> {
+    let started = Instant::now();
+    let mut dependencies = Vec::new();
+    parser.parse_each(reader, &mut |dependency| {
+        dependencies.push(dependency);
+        if let Some(progress) = progress.as_mut() {
+            if dependencies.len() % progress.interval == 0 {
+                (progress.callback)(dependencies.len(), started.elapsed());
+            }
+        }
+    })?;
+
+    let mut tree = MerkleTree::new().with_empty_root();
+    tree.build_from_dependencies(dependencies)
+        .expect("an empty-root tree builds from any dependency list");
+    Ok(tree.root_hash().as_deref() == Some(expected_root))
+}
+
+/// Builds the error for a JSON entry lacking a `version`
+///
+/// serde_json's `Value` keeps no positions, so the line is reported as 0.
+fn missing_version(msg: String) -> ParseError {
+    ParseError::Syntax { line: 0, msg }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_from_in_memory_buffers() {
+        use std::io::Cursor;
+
+        let requirements = b"# pinned deps\nrequests==2.31.0\n\nurllib3 == 2.0.7  # transitive\n";
+        let deps = PipLockfileParser
+            .parse_reader(Cursor::new(&requirements[..]))
+            .unwrap();
+        assert_eq!(deps.len(), 2);
+        assert_eq!(deps[0].name, "requests");
+        assert_eq!(deps[1].version, "2.0.7");
+
+        let lockfile = br#"{
+            "lockfileVersion": 1,
+            "dependencies": {
+                "left-pad": {
+                    "version": "1.3.0",
+                    "resolved": "https://registry.npmjs.org/left-pad/-/left-pad-1.3.0.tgz",
+                    "integrity": "sha512-abc"
+                }
+            }
+        }"#;
+        let deps = NpmLockfileParser
+            .parse_reader(Cursor::new(&lockfile[..]))
+            .unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].integrity.as_deref(), Some("sha512-abc"));
+    }
+
+    #[test]
+    fn test_packages_only_lockfile_with_scoped_nested_deps() {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/package-lock-v3.json");
+        let deps = NpmLockfileParser.parse_lockfile(fixture).unwrap();
+
+        let names: Vec<(&str, &str)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("@babel/core", "7.23.2"),
+                ("semver", "6.3.1"),
+                ("left-pad", "1.3.0"),
+                ("@types/node", "20.8.7"),
+            ]
+        );
+        assert!(deps.iter().all(|d| d.integrity.is_some()));
+    }
+
+    #[test]
+    fn test_parse_each_matches_batch_parse() {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/package-lock-v3.json");
+        let batch = NpmLockfileParser.parse_lockfile(&fixture).unwrap();
+
+        let mut visited = Vec::new();
+        let reader = BufReader::new(File::open(&fixture).unwrap());
+        NpmLockfileParser
+            .parse_each(reader, &mut |dep| visited.push(dep))
+            .unwrap();
+        assert_eq!(visited, batch);
+
+        let requirements = &b"requests==2.31.0\nurllib3==2.0.7\n"[..];
+        let mut count = 0;
+        PipLockfileParser
+            .parse_each(requirements, &mut |_| count += 1)
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_corrupt_lockfile_reports_syntax_line() {
+        // Missing comma at the end of line 4; the parser trips over line 5
+        let corrupt = &b"{\n  \"lockfileVersion\": 1,\n  \"dependencies\": {\n    \"left-pad\": { \"version\": \"1.3.0\" }\n    \"semver\": { \"version\": \"6.3.1\" }\n  }\n}\n"[..];
+        match NpmLockfileParser.parse_reader(corrupt) {
+            Err(ParseError::Syntax { line, .. }) => assert_eq!(line, 5),
+            other => panic!("expected syntax error, got {:?}", other),
+        }
+
+        let future = &b"{\"lockfileVersion\": 9, \"packages\": {}}"[..];
+        assert!(matches!(
+            NpmLockfileParser.parse_reader(future),
+            Err(ParseError::UnsupportedVersion(9))
+        ));
+
+        let unpinned = &b"requests==2.31.0\nflask\n"[..];
+        assert!(matches!(
+            PipLockfileParser.parse_reader(unpinned),
+            Err(ParseError::Syntax { line: 2, .. })
+        ));
+
+        assert!(matches!(
+            NpmLockfileParser.parse_lockfile("/nonexistent/package-lock.json"),
+            Err(ParseError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_lockfile_reports_progress() {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/package-lock-v3.json");
+        let mut tree = MerkleTree::new();
+        tree.build_from_dependencies(NpmLockfileParser.parse_lockfile(&fixture).unwrap())
+            .unwrap();
+        let root = tree.root_hash().unwrap();
+
+        let mut reported = Vec::new();
+        let mut record = |processed: usize, _elapsed: Duration| reported.push(processed);
+        let reader = BufReader::new(File::open(&fixture).unwrap());
+        let progress = ProgressReporter::new(2, &mut record);
+        assert!(verify_lockfile(&NpmLockfileParser, reader, &root, Some(progress)).unwrap());
+        assert_eq!(reported, vec![2, 4]);
+
+        let reader = BufReader::new(File::open(&fixture).unwrap());
+        assert!(!verify_lockfile(&NpmLockfileParser, reader, &[0u8; 32], None).unwrap());
+    }
+
+    #[test]
+    fn test_hostile_inputs_error_without_panicking() {
+        let lockfile =
+            br#"{"lockfileVersion": 1, "dependencies": {"left-pad": {"version": "1.3.0"}}}"#;
+        for end in 0..lockfile.len() {
+            assert!(matches!(
+                NpmLockfileParser.parse_reader(&lockfile[..end]),
+                Err(ParseError::Syntax { .. })
+            ));
+        }
+
+        // serde_json's recursion limit stops this long before the stack runs out
+        let depth = 100_000;
+        let mut nested = br#"{"lockfileVersion": 1, "dependencies": "#.to_vec();
+        nested.extend(b"{\"a\": {\"version\": \"1\", \"dependencies\": ".repeat(depth));
+        nested.extend(b"{}");
+        nested.extend(b"}}".repeat(depth));
+        nested.push(b'}');
+        assert!(matches!(
+            NpmLockfileParser.parse_reader(&nested[..]),
+            Err(ParseError::Syntax { .. })
+        ));
+
+        let not_utf8 = &b"requests==2.31.0\nfla\xffsk==3.0.0\n"[..];
+        assert!(matches!(
+            PipLockfileParser.parse_reader(not_utf8),
+            Err(ParseError::Syntax { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_requirements_extras_and_markers_are_separated() {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/requirements-markers.txt");
+        let deps = PipLockfileParser.parse_lockfile(fixture).unwrap();
+
+        let parsed: Vec<(&str, &str, Option<&str>)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str(), d.marker.as_deref()))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                ("requests", "2.31.0", None),
+                (
+                    "importlib-metadata",
+                    "6.8.0",
+                    Some("python_version < \"3.10\"")
+                ),
+                ("uvicorn", "0.23.2", Some("sys_platform != \"win32\"")),
+                ("urllib3", "2.0.7", None),
+            ]
+        );
+    }
+}
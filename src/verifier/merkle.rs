@@ -1,30 +1,65 @@
 //! Merkle Tree implementation for deterministic dependency verification
 //!
-//! This module provides a cryptographically secure Merkle tree implementation
-//! using ring for hashing and std for core functionality.
-
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Result as IoResult};
-use std::path::Path;
-use ring::digest::{self, SHA256};
-
-/// Trait for parsing lockfiles from different package managers
-pub trait LockfileParser {
-    /// Parses a lockfile and extracts dependency information
-    fn parse_lockfile<P: AsRef<Path>>(&self, path: P) -> IoResult<Vec<Dependency>>;
-    
-    /// Returns the package manager type
-    fn package_manager(&self) -> &'static str;
-}
+//! This module provides a cryptographically secure Merkle tree implementation,
+//! hashing with SHA-256 unless another `DeterministicHasher` is injected. It
+//! only needs `alloc`, so tree construction and inclusion checks are available
+//! without the `std` feature.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use bitflags::bitflags;
+
+use super::attestation::{RootSigningKey, Signature};
+use super::proof;
+pub use super::proof::{Proof, ProofStep, Side};
+use crate::hasher::{Blake3Hasher, DeterministicHasher, Sha256Hasher};
+
+#[cfg(feature = "std")]
+pub use super::lockfile::{LockfileParser, NpmLockfileParser, PipLockfileParser};
 
 /// Represents a dependency with its metadata
+///
+/// Fields may hold any characters, including `:` and NUL: leaves hash each
+/// field length-prefixed rather than joining fields with a separator, so no
+/// content can shift bytes from one field into another. Trees built from
+/// such dependencies need no validation or escaping.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Dependency {
     pub name: String,
     pub version: String,
     pub integrity: Option<String>,
     pub resolved: Option<String>,
+    /// Environment marker restricting where the dependency is installed,
+    /// e.g. `python_version < "3.9"`
+    ///
+    /// Recorded for consumers but never hashed into a leaf, so adding marker
+    /// support leaves existing roots unchanged.
+    pub marker: Option<String>,
+}
+
+bitflags! {
+    /// Selects which dependency fields feed each leaf hash
+    ///
+    /// Dropping `RESOLVED`, for example, yields roots that do not depend on
+    /// which registry mirror a lockfile was resolved against.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct LeafFields: u8 {
+        const NAME = 1 << 0;
+        const VERSION = 1 << 1;
+        const INTEGRITY = 1 << 2;
+        const RESOLVED = 1 << 3;
+    }
+}
+
+impl Default for LeafFields {
+    fn default() -> Self {
+        Self::all()
+    }
 }
 
 /// Node in the Merkle tree
@@ -36,21 +71,72 @@ pub struct MerkleNode {
 }
 
 /// Merkle tree for dependency verification
-#[derive(Debug)]
 pub struct MerkleTree {
     root: Option<MerkleNode>,
     leaves: Vec<Vec<u8>>,
+    pub(super) leaf_fields: LeafFields,
+    hasher: Box<dyn DeterministicHasher>,
+    empty_root: bool,
+}
+
+impl fmt::Debug for MerkleTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MerkleTree")
+            .field("root", &self.root)
+            .field("leaves", &self.leaves)
+            .field("leaf_fields", &self.leaf_fields)
+            .field("empty_root", &self.empty_root)
+            .finish_non_exhaustive()
+    }
 }
 
 impl MerkleTree {
     /// Creates a new empty Merkle tree
     pub fn new() -> Self {
+        Self::with_leaf_fields(LeafFields::default())
+    }
+
+    /// Creates an empty tree whose leaves hash only the selected fields
+    pub fn with_leaf_fields(leaf_fields: LeafFields) -> Self {
         Self {
             root: None,
             leaves: Vec::new(),
+            leaf_fields,
+            hasher: Box::new(Sha256Hasher::default()),
+            empty_root: false,
         }
     }
-    
+
+    /// Replaces the hash function used for leaves and interior nodes
+    ///
+    /// Call before building; proofs only verify against a tree using the same
+    /// hasher.
+    pub fn with_hasher(mut self, hasher: impl DeterministicHasher + 'static) -> Self {
+        self.hasher = Box::new(hasher);
+        self
+    }
+
+    /// Hashes leaves and interior nodes with BLAKE3 keyed by `key`
+    ///
+    /// Trees over the same dependencies under different keys have unrelated
+    /// roots, so roots from different tenants cannot be compared. This
+    /// replaces any hasher set with `with_hasher`; proofs verify with
+    /// `proof::verify_proof_with` and `Blake3Hasher::keyed(key)`.
+    pub fn with_key(self, key: &[u8]) -> Self {
+        self.with_hasher(Blake3Hasher::keyed(key))
+    }
+
+    /// Gives a tree without dependencies a defined root instead of `None`
+    ///
+    /// The empty root is the hasher's digest of the empty string; for the
+    /// default SHA-256 that is `e3b0c442...7852b855`. Building from an empty
+    /// dependency list then succeeds, and inclusion checks against the empty
+    /// tree still fail.
+    pub fn with_empty_root(mut self) -> Self {
+        self.empty_root = true;
+        self
+    }
+
     /// Builds the Merkle tree from a list of dependencies
     pub fn build_from_dependencies(&mut self, dependencies: Vec<Dependency>) -> Result<(), String> {
         // Convert dependencies to leaf hashes
@@ -58,28 +144,82 @@ impl MerkleTree {
             .into_iter()
             .map(|dep| self.hash_dependency(&dep))
             .collect();
-        
+
         if self.leaves.is_empty() {
+            self.root = None;
+            if self.empty_root {
+                return Ok(());
+            }
             return Err("Cannot build tree from empty dependency list".to_string());
         }
-        
+
         // Build the tree bottom-up
         self.root = Some(self.build_tree_recursive(&self.leaves));
         Ok(())
     }
-    
+
     /// Calculates the root hash of the Merkle tree
     pub fn root_hash(&self) -> Option<Vec<u8>> {
-        self.root.as_ref().map(|node| node.hash.clone())
+        match &self.root {
+            Some(node) => Some(node.hash.clone()),
+            None if self.empty_root => Some(self.hasher.clone_state().finalize()),
+            None => None,
+        }
     }
-    
+
     /// Returns the root hash as a hexadecimal string
     pub fn root_hash_hex(&self) -> Option<String> {
-        self.root_hash().map(|hash| hex::encode(hash))
+        self.root_hash().map(hex::encode)
+    }
+
+    /// Index of the first of `roots` equal to this tree's root, if any
+    ///
+    /// Useful when a dependency set may legitimately match one of several
+    /// published roots, for example one per leaf-field policy.
+    pub fn verify_against_any(&self, roots: &[Vec<u8>]) -> Option<usize> {
+        let root = self.root_hash()?;
+        roots.iter().position(|candidate| *candidate == root)
+    }
+
+    /// Signs the root hash, or returns `None` for an empty tree without an
+    /// empty root
+    ///
+    /// Consumers check the result with `attestation::verify_signed_root`.
+    pub fn sign_root(&self, key: &RootSigningKey) -> Option<Signature> {
+        self.root_hash().map(|root| key.sign(&root))
     }
-    
+
+    /// Number of leaves (dependencies) in the tree
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Number of hashing levels between the leaves and the root
+    ///
+    /// This is the length of the longest inclusion proof: a single-leaf tree
+    /// has depth 0, and the `len.div_ceil(2)` split gives `ceil(log2(len))`.
+    pub fn depth(&self) -> usize {
+        self.root.as_ref().map_or(0, Self::node_depth)
+    }
+
+    /// Node hashes level by level, from the leaf hashes up to the root
+    ///
+    /// Level `k` lists, left to right, every node `k` levels above the leaves.
+    /// A subtree shorter than its sibling has its top node carried up
+    /// unchanged, so each level spans every leaf and there are `depth() + 1`
+    /// levels. A tree without nodes has no levels, even with `with_empty_root`.
+    pub fn layers(&self) -> Vec<Vec<Vec<u8>>> {
+        self.root.as_ref().map_or_else(Vec::new, Self::node_layers)
+    }
+
     /// Verifies a dependency is included in the tree (proof of inclusion)
-    pub fn verify_inclusion(&self, dependency: &Dependency, proof: &[Vec<u8>]) -> bool {
+    ///
+    /// Proofs with more steps than the tree has levels cannot be valid and are
+    /// rejected before any hashing, bounding the work an oversized proof costs.
+    pub fn verify_inclusion(&self, dependency: &Dependency, proof: &[ProofStep]) -> bool {
+        if proof.len() > self.proof_size() {
+            return false;
+        }
         if let Some(root) = &self.root {
             let leaf_hash = self.hash_dependency(dependency);
             self.verify_proof(&leaf_hash, proof, &root.hash)
@@ -87,38 +227,156 @@ impl MerkleTree {
             false
         }
     }
-    
+
+    /// Verifies a stream of inclusion proofs against this tree's root
+    ///
+    /// Proofs are pulled from the iterator one at a time, so arbitrarily large
+    /// proof sets can be checked without collecting them in memory first.
+    pub fn verify_inclusions<'a, I>(&'a self, proofs: I) -> impl Iterator<Item = bool> + 'a
+    where
+        I: Iterator<Item = (Dependency, Vec<ProofStep>)> + 'a,
+    {
+        proofs.map(move |(dependency, proof)| self.verify_inclusion(&dependency, &proof))
+    }
+
+    /// Sibling hashes in the longest proof the tree can produce, `ceil(log2(leaf_count))`
+    ///
+    /// Computed from the leaf count alone, unlike `depth`, which walks the
+    /// tree, so callers can size buffers before generating any proof. Every
+    /// proof has exactly this length when the leaf count is a power of two;
+    /// otherwise the `ceil(n/2)` split keeps the tree balanced and some
+    /// proofs are one step shorter.
+    pub fn proof_size(&self) -> usize {
+        (usize::BITS - self.leaves.len().saturating_sub(1).leading_zeros()) as usize
+    }
+
+    /// Sibling bytes in the longest proof, `proof_size` times the digest length
+    ///
+    /// The `Proof::to_bytes` encoding adds a 5-byte header and one side bit
+    /// per step on top of this.
+    pub fn proof_bytes_estimate(&self) -> usize {
+        self.proof_size() * self.hasher.output_len()
+    }
+
     /// Generates a proof of inclusion for a given dependency
-    pub fn generate_proof(&self, dependency: &Dependency) -> Option<Vec<Vec<u8>>> {
+    pub fn generate_proof(&self, dependency: &Dependency) -> Option<Vec<ProofStep>> {
         let leaf_hash = self.hash_dependency(dependency);
-        
+
         // Find the index of the leaf
         let leaf_index = self.leaves.iter().position(|h| h == &leaf_hash)?;
-        
+
+        self.generate_proof_at_index(leaf_index)
+    }
+
+    /// Generates a proof of inclusion for the leaf at `leaf_index`
+    ///
+    /// Unlike `generate_proof`, this addresses a position directly, so repeated
+    /// or colliding leaves can each be proven at their own index.
+    pub fn generate_proof_at_index(&self, leaf_index: usize) -> Option<Vec<ProofStep>> {
+        if leaf_index >= self.leaves.len() {
+            return None;
+        }
+
         // Generate proof path
-        self.generate_proof_recursive(&self.root.as_ref()?, leaf_index, self.leaves.len())
+        self.generate_proof_recursive(self.root.as_ref()?, leaf_index, self.leaves.len())
+    }
+
+    /// Replaces the leaf at `leaf_index`, rehashing only the path to the root
+    ///
+    /// Returns the roots before and after the update, or `None` if the index
+    /// is out of range. The result matches a full rebuild with the new
+    /// dependency at that position.
+    pub fn update_leaf(
+        &mut self,
+        leaf_index: usize,
+        dependency: &Dependency,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        if leaf_index >= self.leaves.len() {
+            return None;
+        }
+
+        let leaf_hash = self.hash_dependency(dependency);
+        let mut root = self.root.take()?;
+        let old_root = root.hash.clone();
+        self.update_recursive(&mut root, leaf_index, self.leaves.len(), &leaf_hash);
+        let new_root = root.hash.clone();
+
+        self.root = Some(root);
+        self.leaves[leaf_index] = leaf_hash;
+        Some((old_root, new_root))
+    }
+
+    /// Checks that a single leaf changed from `old` to `new` between two roots
+    ///
+    /// Updating one leaf leaves every sibling on its path untouched, so one
+    /// proof, such as `generate_proof_at_index` after `update_leaf`, must take
+    /// the old leaf to `old_root` and the new leaf to `new_root`.
+    pub fn verify_leaf_update(
+        &self,
+        old_root: &[u8],
+        new_root: &[u8],
+        old: &Dependency,
+        new: &Dependency,
+        proof: &[ProofStep],
+    ) -> bool {
+        proof.len() <= self.proof_size()
+            && self.verify_proof(&self.hash_dependency(old), proof, old_root)
+            && self.verify_proof(&self.hash_dependency(new), proof, new_root)
+    }
+
+    /// Renders the tree as an indented ASCII layout of truncated node hashes
+    ///
+    /// Intended for diagnosing root mismatches during development; the format
+    /// is not stable and should not be parsed.
+    pub fn debug_dump(&self) -> String {
+        let mut output = String::new();
+        match &self.root {
+            Some(root) => Self::dump_node(root, "", "", &mut output),
+            None => output.push_str("(empty tree)\n"),
+        }
+        output
     }
-    
+
     /// Private method to hash a dependency consistently
-    fn hash_dependency(&self, dependency: &Dependency) -> Vec<u8> {
-        let mut context = digest::Context::new(&SHA256);
-        context.update(dependency.name.as_bytes());
-        context.update(b":");
-        context.update(dependency.version.as_bytes());
-        
-        if let Some(integrity) = &dependency.integrity {
-            context.update(b":");
-            context.update(integrity.as_bytes());
+    ///
+    /// Every field is written as its big-endian `u64` byte length followed by
+    /// the bytes, and optional fields carry a presence byte, so no two distinct
+    /// dependencies share an encoding regardless of the characters they contain.
+    /// Fields excluded by the tree's `LeafFields` are skipped entirely.
+    pub(super) fn hash_dependency(&self, dependency: &Dependency) -> Vec<u8> {
+        let mut state = self.hasher.clone_state();
+        if self.leaf_fields.contains(LeafFields::NAME) {
+            Self::update_field(state.as_mut(), dependency.name.as_bytes());
+        }
+        if self.leaf_fields.contains(LeafFields::VERSION) {
+            Self::update_field(state.as_mut(), dependency.version.as_bytes());
+        }
+        if self.leaf_fields.contains(LeafFields::INTEGRITY) {
+            Self::update_optional_field(state.as_mut(), dependency.integrity.as_deref());
         }
-        
-        if let Some(resolved) = &dependency.resolved {
-            context.update(b":");
-            context.update(resolved.as_bytes());
+        if self.leaf_fields.contains(LeafFields::RESOLVED) {
+            Self::update_optional_field(state.as_mut(), dependency.resolved.as_deref());
         }
-        
-        context.finish().as_ref().to_vec()
+        state.finalize()
     }
-    
+
+    /// Feeds a length-prefixed field into the hash state
+    fn update_field(state: &mut dyn DeterministicHasher, field: &[u8]) {
+        state.update(&(field.len() as u64).to_be_bytes());
+        state.update(field);
+    }
+
+    /// Feeds an optional field, distinguishing `None` from an empty value
+    fn update_optional_field(state: &mut dyn DeterministicHasher, field: Option<&str>) {
+        match field {
+            Some(value) => {
+                state.update(&[1]);
+                Self::update_field(state, value.as_bytes());
+            }
+            None => state.update(&[0]),
+        }
+    }
+
     /// Recursively builds the Merkle tree
     fn build_tree_recursive(&self, hashes: &[Vec<u8>]) -> MerkleNode {
         if hashes.len() == 1 {
@@ -128,127 +386,222 @@ impl MerkleTree {
                 right: None,
             };
         }
-        
-        let mid = (hashes.len() + 1) / 2;
+
+        let mid = hashes.len().div_ceil(2);
         let left_child = self.build_tree_recursive(&hashes[..mid]);
         let right_child = if mid < hashes.len() {
             Some(self.build_tree_recursive(&hashes[mid..]))
         } else {
             None
         };
-        
+
         let combined_hash = if let Some(ref right) = right_child {
             self.hash_combine(&left_child.hash, &right.hash)
         } else {
             left_child.hash.clone()
         };
-        
+
         MerkleNode {
             hash: combined_hash,
             left: Some(Box::new(left_child)),
             right: right_child.map(Box::new),
         }
     }
-    
-    /// Combines two hashes using SHA256
+
+    /// Rewrites one leaf below `node` and rehashes the nodes above it
+    fn update_recursive(
+        &self,
+        node: &mut MerkleNode,
+        leaf_index: usize,
+        total_leaves: usize,
+        leaf_hash: &[u8],
+    ) {
+        if total_leaves == 1 {
+            node.hash = leaf_hash.to_vec();
+            return;
+        }
+
+        // Mirror the split used by `build_tree_recursive`
+        let mid = total_leaves.div_ceil(2);
+        if let (Some(left), Some(right)) = (node.left.as_deref_mut(), node.right.as_deref_mut()) {
+            if leaf_index < mid {
+                self.update_recursive(left, leaf_index, mid, leaf_hash);
+            } else {
+                self.update_recursive(right, leaf_index - mid, total_leaves - mid, leaf_hash);
+            }
+            node.hash = self.hash_combine(&left.hash, &right.hash);
+        }
+    }
+
+    /// Combines two hashes using the tree's hasher
     fn hash_combine(&self, left: &[u8], right: &[u8]) -> Vec<u8> {
-        let mut context = digest::Context::new(&SHA256);
-        context.update(left);
-        context.update(right);
-        context.finish().as_ref().to_vec()
+        proof::hash_combine_with(self.hasher.as_ref(), left, right)
     }
-    
+
     /// Verifies a Merkle proof
-    fn verify_proof(&self, leaf_hash: &[u8], proof: &[Vec<u8>], root_hash: &[u8]) -> bool {
-        let mut current_hash = leaf_hash.to_vec();
-        
-        for sibling_hash in proof {
-            current_hash = self.hash_combine(&current_hash, sibling_hash);
-        }
-        
-        current_hash == root_hash
+    pub(super) fn verify_proof(
+        &self,
+        leaf_hash: &[u8],
+        proof: &[ProofStep],
+        root_hash: &[u8],
+    ) -> bool {
+        proof::verify_proof_with(self.hasher.as_ref(), leaf_hash, proof, root_hash)
     }
-    
+
     /// Recursively generates proof of inclusion
     fn generate_proof_recursive(
         &self,
         node: &MerkleNode,
         leaf_index: usize,
         total_leaves: usize,
-    ) -> Option<Vec<Vec<u8>>> {
-        // Implementation would go here - simplified for scaffolding
-        // This is a complex recursive algorithm that tracks the path
-        // from leaf to root and collects sibling hashes
-        Some(Vec::new())
+    ) -> Option<Vec<ProofStep>> {
+        if total_leaves == 1 {
+            return Some(Vec::new());
+        }
+
+        // Mirror the split used by `build_tree_recursive`
+        let mid = total_leaves.div_ceil(2);
+        let left = node.left.as_deref()?;
+        let right = node.right.as_deref()?;
+
+        let (mut proof, step) = if leaf_index < mid {
+            let proof = self.generate_proof_recursive(left, leaf_index, mid)?;
+            (
+                proof,
+                ProofStep {
+                    sibling: right.hash.clone(),
+                    side: Side::Right,
+                },
+            )
+        } else {
+            let proof =
+                self.generate_proof_recursive(right, leaf_index - mid, total_leaves - mid)?;
+            (
+                proof,
+                ProofStep {
+                    sibling: left.hash.clone(),
+                    side: Side::Left,
+                },
+            )
+        };
+
+        proof.push(step);
+        Some(proof)
     }
-}
 
-impl Default for MerkleTree {
-    fn default() -> Self {
-        Self::new()
+    /// Longest path from `node` down to a leaf
+    fn node_depth(node: &MerkleNode) -> usize {
+        let left = node
+            .left
+            .as_deref()
+            .map_or(0, |child| 1 + Self::node_depth(child));
+        let right = node
+            .right
+            .as_deref()
+            .map_or(0, |child| 1 + Self::node_depth(child));
+        left.max(right)
     }
-}
 
-/// npm lockfile parser implementation
-pub struct NpmLockfileParser;
-
-impl LockfileParser for NpmLockfileParser {
-    fn parse_lockfile<P: AsRef<Path>>(&self, path: P) -> IoResult<Vec<Dependency>> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let mut dependencies = Vec::new();
-        
-        // Simple parsing implementation - would need proper JSON parsing
-        // This is scaffolding code
-        for line in reader.lines() {
-            let line = line?;
-            // Parse npm lockfile format
-            // Implementation would extract name, version, integrity, resolved
+    /// Layers of the subtree under `node`, carrying the shorter side up
+    fn node_layers(node: &MerkleNode) -> Vec<Vec<Vec<u8>>> {
+        let (Some(left), Some(right)) = (node.left.as_deref(), node.right.as_deref()) else {
+            // A leaf, or a lone child whose hash this node repeats
+            return match node.left.as_deref() {
+                Some(child) => Self::node_layers(child),
+                None => vec![vec![node.hash.clone()]],
+            };
+        };
+
+        let mut left = Self::node_layers(left);
+        let mut right = Self::node_layers(right);
+        let height = left.len().max(right.len());
+        for side in [&mut left, &mut right] {
+            while side.len() < height {
+                let top = side[side.len() - 1].clone();
+                side.push(top);
+            }
         }
-        
-        Ok(dependencies)
-    }
-    
-    fn package_manager(&self) -> &'static str {
-        "npm"
+
+        let mut layers: Vec<Vec<Vec<u8>>> = left
+            .into_iter()
+            .zip(right)
+            .map(|(mut level, right)| {
+                level.extend(right);
+                level
+            })
+            .collect();
+        layers.push(vec![node.hash.clone()]);
+        layers
     }
-}
 
-/// pip requirements.txt parser implementation
-pub struct PipLockfileParser;
-
-impl LockfileParser for PipLockfileParser {
-    fn parse_lockfile<P: AsRef<Path>>(&self, path: P) -> IoResult<Vec<Dependency>> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let mut dependencies = Vec::new();
-        
-        // Simple parsing implementation - would need proper requirements.txt parsing
-        // This is scaffolding code
-        for line in reader.lines() {
-            let line = line?;
-            // Parse requirements.txt format
-            // Implementation would extract name, version
+    /// Number of hex characters shown per hash in `debug_dump`
+    const DUMP_HEX_LEN: usize = 16;
+
+    /// Writes one node and its children to the debug dump
+    fn dump_node(node: &MerkleNode, connector: &str, child_prefix: &str, output: &mut String) {
+        let kind = if node.left.is_none() && node.right.is_none() {
+            "leaf"
+        } else {
+            "node"
+        };
+        let hex_hash = hex::encode(&node.hash);
+        output.push_str(&format!(
+            "{}{} {}\n",
+            connector,
+            kind,
+            &hex_hash[..Self::DUMP_HEX_LEN.min(hex_hash.len())]
+        ));
+
+        let children: Vec<&MerkleNode> = [&node.left, &node.right]
+            .into_iter()
+            .filter_map(|child| child.as_deref())
+            .collect();
+        for (i, child) in children.iter().enumerate() {
+            let is_last = i + 1 == children.len();
+            let (branch, extension) = if is_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            Self::dump_node(
+                child,
+                &format!("{}{}", child_prefix, branch),
+                &format!("{}{}", child_prefix, extension),
+                output,
+            );
         }
-        
-        Ok(dependencies)
     }
-    
-    fn package_manager(&self) -> &'static str {
-        "pip"
+}
+
+impl Default for MerkleTree {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    
+
+    /// `n` unpinned dependencies named `pkg-0` .. `pkg-{n-1}`, all at `1.0.0`.
+    pub(crate) fn numbered_dependencies(n: usize) -> Vec<Dependency> {
+        (0..n)
+            .map(|i| Dependency {
+                name: format!("pkg-{}", i),
+                version: "1.0.0".to_string(),
+                integrity: None,
+                resolved: None,
+                marker: None,
+            })
+            .collect()
+    }
+
     #[test]
     fn test_merkle_tree_creation() {
-        let mut tree = MerkleTree::new();
+        let tree = MerkleTree::new();
         assert!(tree.root_hash().is_none());
     }
-    
+
     #[test]
     fn test_dependency_hashing() {
         let tree = MerkleTree::new();
@@ -257,13 +610,76 @@ mod tests {
             version: "1.0.0".to_string(),
             integrity: Some("sha512-abc123".to_string()),
             resolved: None,
+            marker: None,
         };
-        
+
         let hash1 = tree.hash_dependency(&dep);
         let hash2 = tree.hash_dependency(&dep);
         assert_eq!(hash1, hash2, "Dependency hashing should be deterministic");
     }
-    
+
+    #[test]
+    fn test_colon_shifted_fields_do_not_collide() {
+        let tree = MerkleTree::new();
+        let dep_a = Dependency {
+            name: "a".to_string(),
+            version: "b:c".to_string(),
+            integrity: None,
+            resolved: None,
+            marker: None,
+        };
+        let dep_b = Dependency {
+            name: "a:b".to_string(),
+            version: "c".to_string(),
+            integrity: None,
+            resolved: None,
+            marker: None,
+        };
+
+        assert_ne!(
+            tree.hash_dependency(&dep_a),
+            tree.hash_dependency(&dep_b),
+            "Shifting a separator between fields must change the leaf hash"
+        );
+    }
+
+    #[test]
+    fn test_nul_bytes_are_hashed_as_field_content() {
+        let tree = MerkleTree::new();
+        let dependency = |name: &str, version: &str| Dependency {
+            name: name.to_string(),
+            version: version.to_string(),
+            integrity: None,
+            resolved: None,
+            marker: None,
+        };
+
+        // NUL is ordinary content, not a terminator: neither dropping it nor
+        // moving it across the field boundary yields the same leaf
+        let with_nul = tree.hash_dependency(&dependency("left-pad\0", "1.3.0"));
+        assert_ne!(
+            with_nul,
+            tree.hash_dependency(&dependency("left-pad", "1.3.0"))
+        );
+        assert_ne!(
+            with_nul,
+            tree.hash_dependency(&dependency("left-pad", "\u{0}1.3.0"))
+        );
+
+        // Such names are accepted and prove like any other
+        let dependencies = vec![
+            dependency("left-pad\0", "1.3.0"),
+            dependency("scope:pkg", "2.0.0"),
+            dependency("semver", "6.3.1"),
+        ];
+        let mut tree = MerkleTree::new();
+        tree.build_from_dependencies(dependencies.clone()).unwrap();
+        for dependency in &dependencies {
+            let proof = tree.generate_proof(dependency).unwrap();
+            assert!(tree.verify_inclusion(dependency, &proof));
+        }
+    }
+
     #[test]
     fn test_build_tree_from_dependencies() {
         let mut tree = MerkleTree::new();
@@ -273,16 +689,451 @@ mod tests {
                 version: "1.0.0".to_string(),
                 integrity: None,
                 resolved: None,
+                marker: None,
             },
             Dependency {
                 name: "package-b".to_string(),
                 version: "2.0.0".to_string(),
                 integrity: None,
                 resolved: None,
+                marker: None,
             },
         ];
-        
+
         assert!(tree.build_from_dependencies(dependencies).is_ok());
         assert!(tree.root_hash().is_some());
     }
+
+    #[test]
+    fn test_verify_inclusions_streams_results() {
+        let mut tree = MerkleTree::new();
+        let dep_a = Dependency {
+            name: "package-a".to_string(),
+            version: "1.0.0".to_string(),
+            integrity: None,
+            resolved: None,
+            marker: None,
+        };
+        let dep_b = Dependency {
+            name: "package-b".to_string(),
+            version: "2.0.0".to_string(),
+            integrity: None,
+            resolved: None,
+            marker: None,
+        };
+        let unknown = Dependency {
+            name: "package-c".to_string(),
+            version: "3.0.0".to_string(),
+            integrity: None,
+            resolved: None,
+            marker: None,
+        };
+        tree.build_from_dependencies(vec![dep_a.clone(), dep_b.clone()])
+            .unwrap();
+
+        let hash_a = tree.hash_dependency(&dep_a);
+        let hash_b = tree.hash_dependency(&dep_b);
+        let proofs = vec![
+            (
+                dep_a.clone(),
+                vec![ProofStep {
+                    sibling: hash_b.clone(),
+                    side: Side::Right,
+                }],
+            ),
+            (
+                dep_b.clone(),
+                vec![ProofStep {
+                    sibling: hash_a.clone(),
+                    side: Side::Left,
+                }],
+            ),
+            (
+                dep_a,
+                vec![ProofStep {
+                    sibling: hash_b.clone(),
+                    side: Side::Left,
+                }],
+            ),
+            (
+                unknown,
+                vec![ProofStep {
+                    sibling: hash_a,
+                    side: Side::Left,
+                }],
+            ),
+        ];
+
+        let results: Vec<bool> = tree.verify_inclusions(proofs.into_iter()).collect();
+        assert_eq!(results, vec![true, true, false, false]);
+    }
+
+    #[test]
+    fn test_debug_dump_lists_leaves_and_root() {
+        let mut tree = MerkleTree::new();
+        let dependencies = numbered_dependencies(3);
+        tree.build_from_dependencies(dependencies).unwrap();
+
+        let dump = tree.debug_dump();
+        let leaf_lines = dump.lines().filter(|line| line.contains("leaf ")).count();
+        assert_eq!(leaf_lines, 3);
+
+        let root_hex = tree.root_hash_hex().unwrap();
+        assert!(dump
+            .lines()
+            .next()
+            .unwrap()
+            .contains(&root_hex[..MerkleTree::DUMP_HEX_LEN]));
+    }
+
+    #[test]
+    fn test_generate_proof_at_index_for_repeated_leaves() {
+        let mut tree = MerkleTree::new();
+        let dep = Dependency {
+            name: "left-pad".to_string(),
+            version: "1.3.0".to_string(),
+            integrity: None,
+            resolved: None,
+            marker: None,
+        };
+        tree.build_from_dependencies(vec![dep.clone(); 4]).unwrap();
+
+        let proofs: Vec<Vec<ProofStep>> = (0..4)
+            .map(|i| tree.generate_proof_at_index(i).unwrap())
+            .collect();
+        for (i, proof) in proofs.iter().enumerate() {
+            assert!(tree.verify_inclusion(&dep, proof));
+            for other in &proofs[i + 1..] {
+                assert_ne!(proof, other, "Each index should have its own proof path");
+            }
+        }
+        assert!(tree.generate_proof_at_index(4).is_none());
+    }
+
+    #[test]
+    fn test_leaf_count_and_depth() {
+        for (leaves, expected_depth) in [(1, 0), (2, 1), (3, 2), (8, 3)] {
+            let mut tree = MerkleTree::new();
+            let dependencies = numbered_dependencies(leaves);
+            tree.build_from_dependencies(dependencies).unwrap();
+
+            assert_eq!(tree.leaf_count(), leaves);
+            assert_eq!(tree.depth(), expected_depth, "depth for {} leaves", leaves);
+        }
+        assert_eq!(MerkleTree::new().depth(), 0);
+    }
+
+    #[test]
+    fn test_leaf_fields_can_ignore_resolved() {
+        let from_mirror = |url: &str| Dependency {
+            name: "left-pad".to_string(),
+            version: "1.3.0".to_string(),
+            integrity: Some("sha512-abc".to_string()),
+            resolved: Some(url.to_string()),
+            marker: None,
+        };
+        let registry = from_mirror("https://registry.npmjs.org/left-pad/-/left-pad-1.3.0.tgz");
+        let mirror = from_mirror("https://mirror.example.com/left-pad-1.3.0.tgz");
+
+        let full = MerkleTree::new();
+        assert_ne!(
+            full.hash_dependency(&registry),
+            full.hash_dependency(&mirror)
+        );
+
+        let mirror_independent =
+            MerkleTree::with_leaf_fields(LeafFields::all() - LeafFields::RESOLVED);
+        assert_eq!(
+            mirror_independent.hash_dependency(&registry),
+            mirror_independent.hash_dependency(&mirror)
+        );
+    }
+
+    #[test]
+    fn test_empty_tree_root_is_digest_of_empty_string() {
+        let mut tree = MerkleTree::new().with_empty_root();
+        tree.build_from_dependencies(Vec::new()).unwrap();
+
+        assert_eq!(
+            tree.root_hash_hex().as_deref(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(
+            tree.root_hash(),
+            MerkleTree::new().with_empty_root().root_hash()
+        );
+        assert_eq!(tree.leaf_count(), 0);
+
+        let dependency = Dependency {
+            name: "left-pad".to_string(),
+            version: "1.3.0".to_string(),
+            integrity: None,
+            resolved: None,
+            marker: None,
+        };
+        assert!(tree.generate_proof(&dependency).is_none());
+        assert!(!tree.verify_inclusion(&dependency, &[]));
+
+        assert!(MerkleTree::new()
+            .build_from_dependencies(Vec::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify_against_any_finds_matching_root() {
+        let dependencies = vec![Dependency {
+            name: "left-pad".to_string(),
+            version: "1.3.0".to_string(),
+            integrity: Some("sha512-abc".to_string()),
+            resolved: Some("https://mirror.example.com/left-pad-1.3.0.tgz".to_string()),
+            marker: None,
+        }];
+        let mut full = MerkleTree::new();
+        full.build_from_dependencies(dependencies.clone()).unwrap();
+        let mut mirror_independent =
+            MerkleTree::with_leaf_fields(LeafFields::all() - LeafFields::RESOLVED);
+        mirror_independent
+            .build_from_dependencies(dependencies)
+            .unwrap();
+
+        let published = vec![vec![0u8; 32], full.root_hash().unwrap()];
+        assert_eq!(full.verify_against_any(&published), Some(1));
+        assert_eq!(mirror_independent.verify_against_any(&published), None);
+        assert_eq!(MerkleTree::new().verify_against_any(&published), None);
+    }
+
+    #[test]
+    fn test_over_length_proof_is_rejected_without_hashing() {
+        use alloc::sync::Arc;
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        /// SHA-256 that counts how many times it absorbs data
+        struct CountingHasher {
+            inner: Sha256Hasher,
+            updates: Arc<AtomicUsize>,
+        }
+
+        impl DeterministicHasher for CountingHasher {
+            fn update(&mut self, data: &[u8]) {
+                self.updates.fetch_add(1, Ordering::Relaxed);
+                self.inner.update(data);
+            }
+
+            fn finalize(self: Box<Self>) -> Vec<u8> {
+                Box::new(self.inner).finalize()
+            }
+
+            fn clone_state(&self) -> Box<dyn DeterministicHasher> {
+                Box::new(CountingHasher {
+                    inner: self.inner.clone(),
+                    updates: self.updates.clone(),
+                })
+            }
+
+            fn output_len(&self) -> usize {
+                32
+            }
+        }
+
+        let updates = Arc::new(AtomicUsize::new(0));
+        let mut tree = MerkleTree::new().with_hasher(CountingHasher {
+            inner: Sha256Hasher::default(),
+            updates: updates.clone(),
+        });
+        let dependencies = numbered_dependencies(5);
+        tree.build_from_dependencies(dependencies.clone()).unwrap();
+
+        let proof = tree.generate_proof(&dependencies[0]).unwrap();
+        assert_eq!(proof.len(), tree.depth());
+        assert!(tree.verify_inclusion(&dependencies[0], &proof));
+
+        let mut oversized = proof.clone();
+        oversized.extend((0..100_000).map(|_| ProofStep {
+            sibling: vec![0u8; 32],
+            side: Side::Left,
+        }));
+        updates.store(0, Ordering::Relaxed);
+        assert!(!tree.verify_inclusion(&dependencies[0], &oversized));
+        assert_eq!(updates.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_every_leaf_proves_for_uneven_splits() {
+        for leaves in [3, 5, 6, 7] {
+            let dependencies = numbered_dependencies(leaves);
+            let mut tree = MerkleTree::new();
+            tree.build_from_dependencies(dependencies.clone()).unwrap();
+
+            for (index, dependency) in dependencies.iter().enumerate() {
+                let proof = tree.generate_proof_at_index(index).unwrap();
+                assert!(proof.len() <= tree.depth(), "leaf {} of {}", index, leaves);
+                assert!(
+                    tree.verify_inclusion(dependency, &proof),
+                    "leaf {} of {}",
+                    index,
+                    leaves
+                );
+
+                // The proof is tied to its own leaf
+                let other = &dependencies[(index + 1) % leaves];
+                assert!(
+                    !tree.verify_inclusion(other, &proof),
+                    "leaf {} of {}",
+                    index,
+                    leaves
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_leaf_matches_full_rebuild() {
+        let mut dependencies = numbered_dependencies(8);
+        let mut tree = MerkleTree::new();
+        tree.build_from_dependencies(dependencies.clone()).unwrap();
+        let original_root = tree.root_hash().unwrap();
+
+        let old = dependencies[5].clone();
+        let new = Dependency {
+            version: "1.0.1".to_string(),
+            ..old.clone()
+        };
+        let (old_root, new_root) = tree.update_leaf(5, &new).unwrap();
+        assert_eq!(old_root, original_root);
+        assert_eq!(tree.root_hash(), Some(new_root.clone()));
+
+        dependencies[5] = new.clone();
+        let mut rebuilt = MerkleTree::new();
+        rebuilt.build_from_dependencies(dependencies).unwrap();
+        assert_eq!(rebuilt.root_hash(), Some(new_root.clone()));
+
+        // One proof links the old leaf to the old root and the new leaf to the new root
+        let proof = tree.generate_proof_at_index(5).unwrap();
+        assert!(tree.verify_leaf_update(&old_root, &new_root, &old, &new, &proof));
+        assert!(!tree.verify_leaf_update(&old_root, &new_root, &new, &old, &proof));
+        assert!(tree.verify_inclusion(&new, &proof));
+
+        assert!(tree.update_leaf(8, &new).is_none());
+    }
+
+    #[test]
+    fn test_layers_run_from_leaf_hashes_to_root() {
+        let build = |leaves: usize| {
+            let dependencies = numbered_dependencies(leaves);
+            let mut tree = MerkleTree::new();
+            tree.build_from_dependencies(dependencies).unwrap();
+            tree
+        };
+
+        for leaves in [1, 2, 5, 8] {
+            let tree = build(leaves);
+            let layers = tree.layers();
+            assert_eq!(layers.len(), tree.depth() + 1, "{} leaves", leaves);
+            assert_eq!(layers[0], tree.leaves);
+            assert_eq!(layers[layers.len() - 1], vec![tree.root_hash().unwrap()]);
+        }
+
+        // Five leaves split 3 + 2 and the three split 2 + 1, so leaf 2 is
+        // carried up to level 1 and the pair over leaves 3 and 4 to level 2
+        let tree = build(5);
+        let layers = tree.layers();
+        let leaf = &tree.leaves;
+        let pair = |a: &[u8], b: &[u8]| tree.hash_combine(a, b);
+        assert_eq!(
+            layers[1],
+            vec![
+                pair(&leaf[0], &leaf[1]),
+                leaf[2].clone(),
+                pair(&leaf[3], &leaf[4])
+            ]
+        );
+        assert_eq!(
+            layers[2],
+            vec![pair(&layers[1][0], &layers[1][1]), layers[1][2].clone()]
+        );
+
+        assert!(MerkleTree::new().layers().is_empty());
+    }
+
+    #[test]
+    fn test_keyed_trees_differ_per_key() {
+        let dependencies = numbered_dependencies(5);
+        let root = |mut tree: MerkleTree| {
+            tree.build_from_dependencies(dependencies.clone()).unwrap();
+            tree.root_hash().unwrap()
+        };
+
+        let tenant_a = root(MerkleTree::new().with_key(b"tenant-a"));
+        assert_eq!(root(MerkleTree::new().with_key(b"tenant-a")), tenant_a);
+        assert_ne!(root(MerkleTree::new().with_key(b"tenant-b")), tenant_a);
+        assert_ne!(
+            root(MerkleTree::new().with_hasher(Blake3Hasher::default())),
+            tenant_a
+        );
+
+        // Proofs check out only under the tree's own key
+        let mut tree = MerkleTree::new().with_key(b"tenant-a");
+        tree.build_from_dependencies(dependencies.clone()).unwrap();
+        let proof = tree.generate_proof(&dependencies[2]).unwrap();
+        let leaf = tree.hash_dependency(&dependencies[2]);
+        assert!(proof::verify_proof_with(
+            &Blake3Hasher::keyed(b"tenant-a"),
+            &leaf,
+            &proof,
+            &tenant_a
+        ));
+        assert!(!proof::verify_proof_with(
+            &Blake3Hasher::keyed(b"tenant-b"),
+            &leaf,
+            &proof,
+            &tenant_a
+        ));
+    }
+
+    #[test]
+    fn test_proof_size_matches_generated_proofs() {
+        let build = |mut tree: MerkleTree, leaves: usize| {
+            let dependencies = numbered_dependencies(leaves);
+            tree.build_from_dependencies(dependencies).unwrap();
+            tree
+        };
+
+        for leaves in [1, 2, 3, 4, 5, 8, 13, 16] {
+            let tree = build(MerkleTree::new(), leaves);
+            let proofs: Vec<Vec<ProofStep>> = (0..leaves)
+                .map(|i| tree.generate_proof_at_index(i).unwrap())
+                .collect();
+            let longest = proofs.iter().max_by_key(|proof| proof.len()).unwrap();
+            assert_eq!(longest.len(), tree.proof_size(), "{} leaves", leaves);
+            assert!(
+                proofs
+                    .iter()
+                    .all(|proof| proof.len() + 1 >= tree.proof_size()),
+                "{} leaves",
+                leaves
+            );
+            if leaves.is_power_of_two() {
+                assert!(
+                    proofs.iter().all(|proof| proof.len() == tree.proof_size()),
+                    "{} leaves",
+                    leaves
+                );
+            }
+
+            let sibling_bytes: usize = longest.iter().map(|step| step.sibling.len()).sum();
+            assert_eq!(
+                sibling_bytes,
+                tree.proof_bytes_estimate(),
+                "{} leaves",
+                leaves
+            );
+        }
+
+        let wide = build(
+            MerkleTree::new().with_hasher(crate::hasher::Sha512Hasher::default()),
+            4,
+        );
+        assert_eq!(wide.proof_bytes_estimate(), 2 * 64);
+        assert_eq!(MerkleTree::new().proof_size(), 0);
+    }
 }
@@ -0,0 +1,19 @@
+//! Dependency verification via Merkle trees over lockfile contents
+//!
+//! `proof`, `merkle`, `pruned` and `attestation` only require `alloc`;
+//! `lockfile` parsing, `file_tree` walking, attestation `bundle`s and the
+//! result-checking `engine` need `std`.
+
+pub mod attestation;
+pub mod merkle;
+pub mod proof;
+pub mod pruned;
+
+#[cfg(feature = "std")]
+pub mod bundle;
+#[cfg(feature = "std")]
+pub mod engine;
+#[cfg(feature = "std")]
+pub mod file_tree;
+#[cfg(feature = "std")]
+pub mod lockfile;
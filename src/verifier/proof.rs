@@ -0,0 +1,238 @@
+//! Allocation-only core of Merkle inclusion proofs
+//!
+//! Everything here depends only on `alloc` and the hashers in `crate::hasher`,
+//! so constrained targets can check proofs without the filesystem or the rest
+//! of `std`.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::hasher::{DeterministicHasher, Sha256Hasher};
+
+/// Position of a proof sibling relative to the hash being folded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Single step of an inclusion proof: a sibling hash and the side it sits on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofStep {
+    pub sibling: Vec<u8>,
+    pub side: Side,
+}
+
+/// Complete inclusion proof with a compact binary encoding
+///
+/// Wire format: step count (u32 BE), digest length (u8), a bitmap with one bit
+/// per step (set for `Side::Left`, least significant bit first), then each
+/// sibling digest in order. All siblings must share one length, as they do in
+/// any proof generated from a `MerkleTree`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Proof {
+    pub steps: Vec<ProofStep>,
+}
+
+/// Failure to encode or decode a `Proof`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofCodecError {
+    /// Sibling digests differ in length or exceed 255 bytes
+    InconsistentDigestLength,
+    /// The input ends before the encoded steps do
+    Truncated,
+    /// Bytes remain after the last encoded step
+    TrailingBytes,
+}
+
+impl fmt::Display for ProofCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InconsistentDigestLength => {
+                f.write_str("proof siblings must share one digest length of at most 255 bytes")
+            }
+            Self::Truncated => f.write_str("encoded proof is truncated"),
+            Self::TrailingBytes => f.write_str("encoded proof has trailing bytes"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProofCodecError {}
+
+impl From<Vec<ProofStep>> for Proof {
+    fn from(steps: Vec<ProofStep>) -> Self {
+        Self { steps }
+    }
+}
+
+impl Proof {
+    /// Encodes the proof in the compact wire format
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProofCodecError> {
+        let digest_len = self.steps.first().map_or(0, |step| step.sibling.len());
+        if digest_len > u8::MAX as usize
+            || self
+                .steps
+                .iter()
+                .any(|step| step.sibling.len() != digest_len)
+        {
+            return Err(ProofCodecError::InconsistentDigestLength);
+        }
+
+        let bitmap_len = self.steps.len().div_ceil(8);
+        let mut bytes = Vec::with_capacity(5 + bitmap_len + self.steps.len() * digest_len);
+        bytes.extend_from_slice(&(self.steps.len() as u32).to_be_bytes());
+        bytes.push(digest_len as u8);
+        let mut bitmap = alloc::vec![0u8; bitmap_len];
+        for (i, step) in self.steps.iter().enumerate() {
+            if step.side == Side::Left {
+                bitmap[i / 8] |= 1 << (i % 8);
+            }
+        }
+        bytes.extend_from_slice(&bitmap);
+        for step in &self.steps {
+            bytes.extend_from_slice(&step.sibling);
+        }
+        Ok(bytes)
+    }
+
+    /// Decodes a proof produced by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofCodecError> {
+        let header: [u8; 4] = bytes
+            .get(..4)
+            .and_then(|header| header.try_into().ok())
+            .ok_or(ProofCodecError::Truncated)?;
+        let count = u32::from_be_bytes(header) as usize;
+        let digest_len = *bytes.get(4).ok_or(ProofCodecError::Truncated)? as usize;
+        let bitmap = bytes
+            .get(5..5 + count.div_ceil(8))
+            .ok_or(ProofCodecError::Truncated)?;
+        let digests = &bytes[5 + bitmap.len()..];
+
+        let expected = count
+            .checked_mul(digest_len)
+            .ok_or(ProofCodecError::Truncated)?;
+        if digests.len() < expected {
+            return Err(ProofCodecError::Truncated);
+        }
+        if digests.len() > expected {
+            return Err(ProofCodecError::TrailingBytes);
+        }
+
+        let steps = (0..count)
+            .map(|i| ProofStep {
+                sibling: digests[i * digest_len..(i + 1) * digest_len].to_vec(),
+                side: if bitmap[i / 8] & (1 << (i % 8)) != 0 {
+                    Side::Left
+                } else {
+                    Side::Right
+                },
+            })
+            .collect();
+        Ok(Self { steps })
+    }
+}
+
+/// Combines two child hashes into their parent using SHA256
+pub fn hash_combine(left: &[u8], right: &[u8]) -> Vec<u8> {
+    hash_combine_with(&Sha256Hasher::default(), left, right)
+}
+
+/// Combines two child hashes using a fresh copy of `hasher`
+pub fn hash_combine_with(hasher: &dyn DeterministicHasher, left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut state = hasher.clone_state();
+    state.update(left);
+    state.update(right);
+    state.finalize()
+}
+
+/// Folds a leaf hash through its proof and compares the result to the root
+pub fn verify_proof(leaf_hash: &[u8], proof: &[ProofStep], root_hash: &[u8]) -> bool {
+    verify_proof_with(&Sha256Hasher::default(), leaf_hash, proof, root_hash)
+}
+
+/// Like `verify_proof`, for trees built with a non-default hasher
+pub fn verify_proof_with(
+    hasher: &dyn DeterministicHasher,
+    leaf_hash: &[u8],
+    proof: &[ProofStep],
+    root_hash: &[u8],
+) -> bool {
+    let mut current_hash = leaf_hash.to_vec();
+
+    for step in proof {
+        current_hash = match step.side {
+            Side::Left => hash_combine_with(hasher, &step.sibling, &current_hash),
+            Side::Right => hash_combine_with(hasher, &current_hash, &step.sibling),
+        };
+    }
+
+    current_hash == root_hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn depth_20_proof() -> Proof {
+        (0..20u8)
+            .map(|i| ProofStep {
+                sibling: hash_combine(&[i], &[i.wrapping_mul(31)]),
+                side: if i % 3 == 0 { Side::Left } else { Side::Right },
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    #[test]
+    fn test_proof_bytes_round_trip() {
+        let proof = depth_20_proof();
+        let bytes = proof.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 4 + 1 + 3 + 20 * 32);
+        assert_eq!(Proof::from_bytes(&bytes).unwrap(), proof);
+
+        assert_eq!(
+            Proof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofCodecError::Truncated)
+        );
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert_eq!(
+            Proof::from_bytes(&padded),
+            Err(ProofCodecError::TrailingBytes)
+        );
+
+        let empty = Proof::default();
+        assert_eq!(
+            Proof::from_bytes(&empty.to_bytes().unwrap()).unwrap(),
+            empty
+        );
+    }
+
+    #[test]
+    fn test_binary_proof_is_under_half_of_hex_json() {
+        let proof = depth_20_proof();
+        let json: Vec<serde_json::Value> = proof
+            .steps
+            .iter()
+            .map(|step| {
+                let side = if step.side == Side::Left {
+                    "left"
+                } else {
+                    "right"
+                };
+                serde_json::json!({ "sibling": hex::encode(&step.sibling), "side": side })
+            })
+            .collect();
+        let json: String = serde_json::to_string(&json).unwrap();
+
+        let binary = proof.to_bytes().unwrap();
+        assert!(
+            binary.len() * 2 < json.len(),
+            "binary {} vs json {}",
+            binary.len(),
+            json.len()
+        );
+    }
+}
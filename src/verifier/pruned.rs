@@ -0,0 +1,118 @@
+//! Pruned Merkle tree views for light-client verification
+//!
+//! A `PrunedTree` holds only a trusted root and the leaves a client cares
+//! about, each with the sibling hashes that connect it to the root. Inclusion
+//! questions about those leaves are answered without the full leaf set.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use super::merkle::{Dependency, LeafFields, MerkleTree};
+use super::proof::ProofStep;
+use crate::hasher::DeterministicHasher;
+
+/// Root of a tree plus proven leaves, without the rest of the tree
+#[derive(Debug)]
+pub struct PrunedTree {
+    root: Vec<u8>,
+    /// Proofs keyed by leaf hash
+    proven: BTreeMap<Vec<u8>, Vec<ProofStep>>,
+    /// Unbuilt tree supplying the leaf fields and hasher
+    settings: MerkleTree,
+}
+
+impl PrunedTree {
+    /// Empty view of the tree with `root`, hashing leaves like `MerkleTree::new`
+    pub fn new(root: Vec<u8>) -> Self {
+        Self {
+            root,
+            proven: BTreeMap::new(),
+            settings: MerkleTree::new(),
+        }
+    }
+
+    /// Hashes only the selected dependency fields, matching the full tree's setting
+    pub fn with_leaf_fields(mut self, leaf_fields: LeafFields) -> Self {
+        self.settings.leaf_fields = leaf_fields;
+        self
+    }
+
+    /// Uses the hash function the full tree was built with
+    pub fn with_hasher(mut self, hasher: impl DeterministicHasher + 'static) -> Self {
+        self.settings = self.settings.with_hasher(hasher);
+        self
+    }
+
+    /// Adds a leaf if `proof` connects it to the root
+    ///
+    /// Returns whether the leaf was accepted; an invalid proof leaves the view
+    /// unchanged.
+    pub fn insert(&mut self, dependency: &Dependency, proof: Vec<ProofStep>) -> bool {
+        let leaf_hash = self.settings.hash_dependency(dependency);
+        if !self.settings.verify_proof(&leaf_hash, &proof, &self.root) {
+            return false;
+        }
+        self.proven.insert(leaf_hash, proof);
+        true
+    }
+
+    /// Whether `dependency` is one of the leaves proven into this view
+    pub fn verify_inclusion(&self, dependency: &Dependency) -> bool {
+        self.proven
+            .contains_key(&self.settings.hash_dependency(dependency))
+    }
+
+    /// The stored proof for `dependency`, for passing on to other verifiers
+    pub fn proof(&self, dependency: &Dependency) -> Option<&[ProofStep]> {
+        self.proven
+            .get(&self.settings.hash_dependency(dependency))
+            .map(Vec::as_slice)
+    }
+
+    /// Root this view verifies against
+    pub fn root_hash(&self) -> &[u8] {
+        &self.root
+    }
+
+    /// Number of proven leaves
+    pub fn len(&self) -> usize {
+        self.proven.len()
+    }
+
+    /// Whether no leaf has been proven yet
+    pub fn is_empty(&self) -> bool {
+        self.proven.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verifier::merkle::tests::numbered_dependencies;
+
+    #[test]
+    fn test_pruned_view_verifies_proven_leaves() {
+        let dependencies = numbered_dependencies(7);
+        let mut tree = MerkleTree::new();
+        tree.build_from_dependencies(dependencies.clone()).unwrap();
+
+        let mut view = PrunedTree::new(tree.root_hash().unwrap());
+        for dependency in [&dependencies[1], &dependencies[5]] {
+            assert!(view.insert(dependency, tree.generate_proof(dependency).unwrap()));
+        }
+
+        assert_eq!(view.len(), 2);
+        assert!(view.verify_inclusion(&dependencies[1]));
+        assert!(view.verify_inclusion(&dependencies[5]));
+        assert!(!view.verify_inclusion(&dependencies[2]));
+        assert_eq!(
+            view.proof(&dependencies[5]),
+            tree.generate_proof(&dependencies[5]).as_deref()
+        );
+
+        // A proof for a different leaf is rejected and leaves the view as it was
+        let borrowed = tree.generate_proof(&dependencies[3]).unwrap();
+        assert!(!view.insert(&dependencies[2], borrowed));
+        assert_eq!(view.len(), 2);
+    }
+}